@@ -1,37 +1,49 @@
-use std::collections::HashMap;
-
 #[path = "./backend/mod.rs"]
 mod backend;
 
 use crate::backend::{print, print_config, quit, run};
-use keymap::DerivedConfig;
-use serde::Deserialize;
+use keymap::{Config, DerivedModalConfig, KeyMapConfig, ModalConfig, ModalKeyMapConfig};
 
 #[derive(keymap::KeyMap, Debug, Hash, Eq, PartialEq, Clone)]
-enum HomeAction {
+enum Action {
     #[key("esc")]
     Quit,
     #[key("e")]
     Edit,
-}
-
-#[derive(keymap::KeyMap, Debug, Hash, Eq, PartialEq, Clone)]
-enum EditAction {
     #[key("esc")]
     Exit,
+    #[key("?")]
+    Help,
 }
 
-#[derive(Deserialize, Debug)]
-#[serde(untagged)]
-enum Actions {
-    Home(DerivedConfig<HomeAction>),
-    Edit(DerivedConfig<EditAction>),
-}
+impl ModalKeyMapConfig<String, Action> for Action {
+    fn modal_keymap_config() -> ModalConfig<String, Action> {
+        let config = |actions: Vec<Action>| {
+            Config::new(
+                actions
+                    .into_iter()
+                    .map(|action| (action.clone(), action.keymap_item()))
+                    .collect(),
+            )
+        };
 
-type Modes = HashMap<String, Actions>;
+        let mut modes = ModalConfig::new();
+        modes.insert("home".into(), config(vec![Action::Quit, Action::Edit]));
+        modes.insert("edit".into(), config(vec![Action::Exit]));
+        modes.insert("global".into(), config(vec![Action::Help]));
+        modes.set_parent(&"home".into(), &"global".into()).unwrap();
+        modes.set_parent(&"edit".into(), &"global".into()).unwrap();
+        modes
+    }
+}
 
 #[allow(unused)]
 pub(crate) const CONFIG: &str = r#"
+base = "home"
+
+[global]
+Help = { description = "Show help" }
+
 [home]
 Quit = { keys = ["esc", "q"], description = "Quit the app" }
 Edit = { keys = ["e"], description = "Enter edit mode" }
@@ -41,35 +53,28 @@ Exit = { keys = ["esc", "q"], description = "Exit edit mode" }
 "#;
 
 fn main() -> std::io::Result<()> {
-    let modes: Modes = toml::from_str(CONFIG).unwrap();
-    let mut mode = "home";
+    let mut modes: DerivedModalConfig<String, Action> = toml::from_str(CONFIG).unwrap();
 
     println!("# Example: Multi-mode application with different key mappings");
 
-    if let Some(Actions::Home(config)) = modes.get("home") {
+    for config in modes.active_configs() {
         print_config(&config.items);
     }
-    println!("\rmode: {mode}");
+    println!("\rmode: home");
 
-    run(move |key| match modes.get(mode).unwrap() {
-        Actions::Home(config) => match config.get(&key) {
-            Some(action) => match action {
-                HomeAction::Quit => quit("quit!"),
-                HomeAction::Edit => {
-                    mode = "edit";
-                    print("enter edit mode!")
-                }
-            },
-            None => print(&format!("{key:?}")),
-        },
-        Actions::Edit(config) => match config.get(&key) {
-            Some(action) => match action {
-                EditAction::Exit => {
-                    mode = "home";
-                    print("exit edit mode!")
-                }
-            },
-            None => print(&format!("{key:?}")),
+    run(move |key| match modes.get(&key).cloned() {
+        Some(action) => match action {
+            Action::Quit => quit("quit!"),
+            Action::Edit => {
+                modes.push(&"edit".to_string()).unwrap();
+                print("enter edit mode!")
+            }
+            Action::Exit => {
+                modes.pop();
+                print("exit edit mode!")
+            }
+            Action::Help => print(&format!("help: {:?}", modes.stack().collect::<Vec<_>>())),
         },
+        None => print(&format!("{key:?}")),
     })
 }
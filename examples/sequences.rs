@@ -4,11 +4,9 @@ mod backend;
 #[path = "./action.rs"]
 mod action;
 
-use std::time::{Duration, Instant};
-
-use crate::backend::{print, print_config, quit, run, Key};
+use crate::backend::{print, print_config, quit, run};
 use action::Action;
-use keymap::{DerivedConfig, KeyMapConfig};
+use keymap::{DerivedConfig, KeyMapConfig, Resolution, Resolver};
 
 // Override default key mapping defined via #[derive(KeyMap)] in Action.
 pub(crate) const CONFIG: &str = r#"
@@ -20,41 +18,31 @@ fn main() -> std::io::Result<()> {
     println!("# Example: Key Sequences (j j)");
 
     let config: DerivedConfig<Action> = toml::from_str(CONFIG).unwrap();
-
-    let mut last_key: Option<Key> = None;
-    let mut last_time = Instant::now();
+    let mut resolver = Resolver::new();
 
     print_config(&config.items);
 
     run(move |key| {
-        let ret = match config.get(&key) {
-            Some(action) => match action {
-                Action::Quit => quit("quit!"),
-                Action::Shoot(_) => print("Shoot!"),
-                Action::Up | Action::Down | Action::Left | Action::Right | Action::Jump => print(
-                    &format!("{action:?} = {}", action.keymap_item().description),
-                ),
-            },
-            None => {
-                // Handle key sequence
-                let Some(last) = last_key else {
-                    // Store last key
-                    last_key = Some(key);
-                    last_time = Instant::now();
-
-                    return false;
-                };
-
-                match config.get_seq(&[last, key]) {
-                    Some(action) => print(&format!("Key sequence: {action:?}")),
-                    None => print(&format!("Unknown key [{key:?}]")),
-                }
-            }
-        };
-
-        // timeout for sequence (e.g., 1 sec)
-        if last_time.elapsed() > Duration::from_secs(1) {
-            last_key = None;
+        let mut ret = false;
+
+        // A single key may complete a sequence, leave it pending, or both
+        // (e.g. a pending `d` followed by `j` fires `d` and then `j`).
+        for resolution in config.resolve(&mut resolver, &key) {
+            ret |= match resolution {
+                Resolution::Matched(action, keys) => match action {
+                    Action::Quit => quit("quit!"),
+                    Action::Shoot(_) => print("Shoot!"),
+                    Action::Jump if keys.len() > 1 => print(&format!("Key sequence: {action:?}")),
+                    Action::Up | Action::Down | Action::Left | Action::Right | Action::Jump => {
+                        print(&format!(
+                            "{action:?} = {}",
+                            action.keymap_item().description
+                        ))
+                    }
+                },
+                Resolution::Pending => print(&format!("Pending [{:?}]", resolver.pending())),
+                Resolution::NoMatch => print(&format!("Unknown key [{key:?}]")),
+            };
         }
 
         ret
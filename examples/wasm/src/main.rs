@@ -1,4 +1,4 @@
-use keymap::{DerivedConfig, ToKeyMap};
+use keymap::{node::State, DerivedConfig, ToKeyMap};
 
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
@@ -31,6 +31,14 @@ pub enum Action {
     #[key("right", symbol = "→", help = "move right")]
     Right,
 
+    /// Stop moving leftward
+    #[key("release-left")]
+    StopLeft,
+
+    /// Stop moving rightward
+    #[key("right:up")]
+    StopRight,
+
     /// Pause
     #[key("p", help = "pause")]
     Pause,
@@ -51,6 +59,8 @@ Jump = { keys = ["space", "up"], symbol = "␣", help = "jump", description = "J
 Quit = { keys = ["q", "esc"], symbol = "↩", help = "quit", description = "Quit!" }
 Left = { keys = ["left", "alt-l"], symbol = "←", help = "move left", description = "Move Left" }
 Right = { keys = ["right", "alt-r"], symbol = "→", help = "move right", description = "Move Right" }
+StopLeft = { keys = ["release-left", "release-alt-l"], description = "Stop moving left" }
+StopRight = { keys = ["right:up", "alt-r:up"], description = "Stop moving right" }
 SelectSkin = { keys = ["@digit"], symbol = "0-9", help = "select skin", description = "Select a skin" }
 "#;
 
@@ -103,12 +113,12 @@ pub fn main() {
 
     let on_keydown = Closure::wrap(Box::new(move |event: KeyboardEvent| {
         event.prevent_default();
-        handle_key_event(&event, true);
+        handle_key_event(&event);
     }) as Box<dyn FnMut(_)>);
 
     let on_keyup = Closure::wrap(Box::new(move |event: KeyboardEvent| {
         event.prevent_default();
-        handle_key_event(&event, false);
+        handle_key_event(&event);
     }) as Box<dyn FnMut(_)>);
 
     document.set_onkeydown(Some(on_keydown.as_ref().unchecked_ref()));
@@ -121,13 +131,13 @@ pub fn main() {
     resetGame();
 }
 
-pub fn handle_key_event(event: &KeyboardEvent, is_keydown: bool) {
+pub fn handle_key_event(event: &KeyboardEvent) {
     let config = derived_config();
     let is_game_over = isGameOver();
+    let key = event.to_keymap().unwrap();
 
     // Log the key that was pressed
-    if is_keydown {
-        let key = event.to_keymap().unwrap();
+    if key.state != Some(State::Released) {
         let mut desc = String::new();
         let mut symbol = String::new();
         let mut help = String::new();
@@ -147,21 +157,15 @@ pub fn handle_key_event(event: &KeyboardEvent, is_keydown: bool) {
     // Use .get_bound() to support Key Group Capturing for SelectSkin
     if let Some(action) = config.get_bound(event) {
         match action {
-            Action::Quit => {
-                if is_keydown {
-                    resetGame();
-                }
-            }
-            Action::SelectSkin(c) => {
-                if is_keydown {
-                    setSkin(c);
-                }
-            }
+            Action::Quit => resetGame(),
+            Action::SelectSkin(c) => setSkin(c),
             _ if !is_game_over => match action {
-                Action::Left => moveLeft(is_keydown),
-                Action::Right => moveRight(is_keydown),
-                Action::Jump if is_keydown => jump(),
-                Action::Pause if is_keydown => pauseGame(),
+                Action::Left => moveLeft(true),
+                Action::Right => moveRight(true),
+                Action::StopLeft => moveLeft(false),
+                Action::StopRight => moveRight(false),
+                Action::Jump => jump(),
+                Action::Pause => pauseGame(),
                 _ => {}
             },
             _ => {}
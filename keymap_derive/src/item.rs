@@ -1,4 +1,4 @@
-use keymap_parser::{parse_seq, Node};
+use keymap_parser::{parse_seq, when, Node};
 use syn::{punctuated::Punctuated, spanned::Spanned, token::Comma, Token, Variant};
 
 /// An attribute path name #[key(...)]
@@ -16,6 +16,10 @@ pub(crate) struct Item<'a> {
     pub description: String,
     pub symbol: Option<String>,
     pub help: Option<String>,
+    /// The raw when clause (e.g. `"editorFocus && !readOnly"`), validated at compile time.
+    pub when: Option<String>,
+    pub group: Option<String>,
+    pub order: Option<i32>,
 }
 
 /// Helper struct representing the arguments parsed from a `#[key(...)]` attribute.
@@ -26,6 +30,11 @@ pub(crate) struct Item<'a> {
 /// 3. Named name-value fields:
 ///    - `symbol = "..."` (e.g. `symbol = "^B"`) defining a custom quick visual symbol for display.
 ///    - `help = "..."` (e.g. `help = "jump"`) defining a short help text description for the binding.
+///    - `when = "..."` (e.g. `when = "editorFocus && !readOnly"`) defining the condition under which
+///      the binding applies.
+///    - `group = "..."` or `category = "..."` (e.g. `group = "Navigation"`) defining the category
+///      of the binding on help screens.
+///    - `order = <int>` (e.g. `order = 1`) defining a sorting hint on help screens.
 ///
 /// Example:
 ///
@@ -37,6 +46,9 @@ struct KeyAttrArgs {
     ignore: bool,
     symbol: Option<String>,
     help: Option<String>,
+    when: Option<syn::LitStr>,
+    group: Option<String>,
+    order: Option<i32>,
 }
 
 impl syn::parse::Parse for KeyAttrArgs {
@@ -45,6 +57,9 @@ impl syn::parse::Parse for KeyAttrArgs {
         let mut ignore = false;
         let mut symbol = None;
         let mut help = None;
+        let mut when = None;
+        let mut group = None;
+        let mut order = None;
 
         while !input.is_empty() {
             if input.peek(syn::LitStr) {
@@ -66,6 +81,20 @@ impl syn::parse::Parse for KeyAttrArgs {
                     let _: Token![=] = input.parse()?;
                     let lit: syn::LitStr = input.parse()?;
                     help = Some(lit.value());
+                } else if ident == "when" {
+                    // Parse 'when = "..."'
+                    let _: Token![=] = input.parse()?;
+                    when = Some(input.parse()?);
+                } else if ident == "group" || ident == "category" {
+                    // Parse 'group = "..."'
+                    let _: Token![=] = input.parse()?;
+                    let lit: syn::LitStr = input.parse()?;
+                    group = Some(lit.value());
+                } else if ident == "order" {
+                    // Parse 'order = 1'
+                    let _: Token![=] = input.parse()?;
+                    let expr: syn::Expr = input.parse()?;
+                    order = Some(parse_order(&expr)?);
                 } else {
                     return Err(syn::Error::new(
                         ident.span(),
@@ -90,10 +119,29 @@ impl syn::parse::Parse for KeyAttrArgs {
             ignore,
             symbol,
             help,
+            when,
+            group,
+            order,
         })
     }
 }
 
+/// Parses an `order` value, which may be a negative integer literal.
+fn parse_order(expr: &syn::Expr) -> syn::Result<i32> {
+    match expr {
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Int(lit),
+            ..
+        }) => lit.base10_parse(),
+        syn::Expr::Unary(syn::ExprUnary {
+            op: syn::UnOp::Neg(_),
+            expr,
+            ..
+        }) => parse_order(expr).map(|order| -order),
+        _ => Err(syn::Error::new(expr.span(), "Expected an integer literal")),
+    }
+}
+
 pub(crate) fn parse_items(
     variants: &Punctuated<Variant, Comma>,
 ) -> Result<Vec<Item<'_>>, syn::Error> {
@@ -105,6 +153,9 @@ pub(crate) fn parse_items(
             let mut ignore = false;
             let mut symbol = None;
             let mut help = None;
+            let mut when = None;
+            let mut group = None;
+            let mut order = None;
 
             for attr in &variant.attrs {
                 if attr.path().is_ident(KEY_IDENT) {
@@ -125,6 +176,19 @@ pub(crate) fn parse_items(
                     if args.help.is_some() {
                         help = args.help;
                     }
+                    if let Some(lit) = args.when {
+                        let expr = lit.value();
+                        when::parse(&expr).map_err(|e| {
+                            syn::Error::new(lit.span(), format!("Invalid when \"{expr}\": {e}"))
+                        })?;
+                        when = Some(expr);
+                    }
+                    if args.group.is_some() {
+                        group = args.group;
+                    }
+                    if args.order.is_some() {
+                        order = args.order;
+                    }
                 }
             }
 
@@ -136,6 +200,9 @@ pub(crate) fn parse_items(
                 nodes,
                 symbol,
                 help,
+                when,
+                group,
+                order,
             })
         })
         .collect()
@@ -50,6 +50,11 @@ mod item;
 /// The `keymap_derive` crate supports the following attributes:
 ///
 /// - `#[key("key")]`: Specifies a key to match.
+/// - `#[key("key", when = "expr")]`: Only applies the binding while `expr`
+///   holds, e.g. `when = "editorFocus && !readOnly"`. See `Config::get_with`.
+/// - `#[key("key", group = "Navigation", order = 1)]`: Sets the category and
+///   sorting hint of the binding on help screens. `category` is an alias of
+///   `group`.
 ///
 #[proc_macro_derive(KeyMap, attributes(key))]
 pub fn keymap(input: TokenStream) -> TokenStream {
@@ -195,6 +200,19 @@ fn impl_keymap_config(name: &Ident, items: &Vec<Item>) -> proc_macro2::TokenStre
                 Some(h) => quote! { .with_help(Some(#h)) },
                 None => quote! {},
             };
+            // The clause was validated at compile time, so parsing cannot fail.
+            let when_opt = match &item.when {
+                Some(w) => quote! { .with_when(::keymap::when::parse(#w).ok()) },
+                None => quote! {},
+            };
+            let group_opt = match &item.group {
+                Some(g) => quote! { .with_group(Some(#g)) },
+                None => quote! {},
+            };
+            let order_opt = match &item.order {
+                Some(o) => quote! { .with_order(Some(#o)) },
+                None => quote! {},
+            };
 
             match_arms_deserialize.push(quote! {
                 #variant_name_str => Ok(#variant_expr_default),
@@ -203,7 +221,7 @@ fn impl_keymap_config(name: &Ident, items: &Vec<Item>) -> proc_macro2::TokenStre
                 #variant_pat => ::keymap::Item::new(
                     vec![#(#keys),*],
                     #doc.to_string()
-                ) #symbol_opt #help_opt,
+                ) #symbol_opt #help_opt #when_opt #group_opt #order_opt,
             });
 
             entries.push(quote! {
@@ -212,7 +230,7 @@ fn impl_keymap_config(name: &Ident, items: &Vec<Item>) -> proc_macro2::TokenStre
                     ::keymap::Item::new(
                         vec![#(#keys),*],
                         #doc.to_string()
-                    ) #symbol_opt #help_opt
+                    ) #symbol_opt #help_opt #when_opt #group_opt #order_opt
                 ),
             });
         }
@@ -58,6 +58,31 @@ enum CustomSymbolTest {
     NoSymbolOrHelp,
 }
 
+#[allow(dead_code)]
+#[derive(Debug, PartialEq, Eq, keymap_derive::KeyMap, Clone)]
+enum WhenTest {
+    /// Insert a newline
+    #[key("enter")]
+    Newline,
+    /// Submit the form
+    #[key("enter", when = "inputFocus && !multiline")]
+    Submit,
+}
+
+#[allow(dead_code)]
+#[derive(Debug, PartialEq, Eq, keymap_derive::KeyMap, Clone)]
+enum GroupTest {
+    /// Move down
+    #[key("j", group = "Navigation", order = 2)]
+    Down,
+    /// Move up
+    #[key("k", category = "Navigation", order = -1)]
+    Up,
+    /// Undo
+    #[key("u")]
+    Undo,
+}
+
 #[cfg(test)]
 mod tests {
     use keymap_dev::{Error, Item, KeyMap, KeyMapConfig, ToKeyMap};
@@ -238,4 +263,47 @@ mod tests {
         assert_eq!(item3.symbol.as_deref(), Some("ctrl-b"));
         assert_eq!(item3.help.as_deref(), None);
     }
+
+    #[test]
+    fn test_when_clause() {
+        let config = WhenTest::keymap_config();
+
+        let (_, item) = &config.items[1];
+        assert_eq!(
+            item.when.as_ref().map(ToString::to_string).as_deref(),
+            Some("inputFocus && !multiline")
+        );
+
+        let key = &wrap("enter")[0];
+        assert_eq!(
+            config.get_with(key, &["inputFocus"][..]),
+            Some(&WhenTest::Submit)
+        );
+        assert_eq!(
+            config.get_with(key, &["inputFocus", "multiline"][..]),
+            Some(&WhenTest::Newline)
+        );
+    }
+
+    #[test]
+    fn test_group_and_order() {
+        let config = GroupTest::keymap_config();
+
+        let (_, item) = &config.items[1];
+        assert_eq!(item.group.as_deref(), Some("Navigation"));
+        assert_eq!(item.order, Some(-1));
+
+        let groups = config
+            .grouped_items()
+            .into_iter()
+            .map(|(group, items)| (group, items.into_iter().map(|(t, _)| t).collect()))
+            .collect::<Vec<(_, Vec<_>)>>();
+        assert_eq!(
+            groups,
+            vec![
+                (None, vec![&GroupTest::Undo]),
+                (Some("Navigation"), vec![&GroupTest::Up, &GroupTest::Down]),
+            ]
+        );
+    }
 }
@@ -29,6 +29,8 @@
 //! ```
 pub mod node;
 pub mod parser;
+pub mod style;
+pub mod when;
 
 pub use node::{Key, Modifier, Modifiers, Node};
-pub use parser::{parse, parse_seq};
+pub use parser::{parse, parse_seq, MAX_FN_KEY};
@@ -8,14 +8,17 @@ use std::{
     ops::BitOr,
 };
 
-use serde::{de, Deserialize, Deserializer};
-use strum_macros::{AsRefStr, Display, EnumString};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use strum_macros::{AsRefStr, Display, EnumIter, EnumMessage, EnumString};
 
 use crate::parse;
 
 /// Separator character used between modifiers and keys in string representations.
 pub(crate) const KEY_SEP: char = '-';
 
+/// Separator character between a key and its state suffix (e.g. `space:up`).
+pub(crate) const STATE_SEP: char = ':';
+
 /// Represents a keyboard input node, consisting of modifier keys and a main key.
 ///
 /// For example, "Ctrl-Shift-A" would be represented as a `Node` with the `Ctrl` and `Shift` modifiers and the `Char('A')` key.
@@ -25,7 +28,9 @@ pub struct Node {
     pub modifiers: Modifiers,
     /// The main key (see [`Key`]).
     pub key: Key,
-
+    /// The key event state (see [`State`]), or `None` if unspecified.
+    ///
+    /// In a pattern, `None` matches presses and repeats but not releases.
     pub state: Option<State>,
 }
 
@@ -38,6 +43,12 @@ impl Node {
             state: None,
         }
     }
+
+    /// Sets the key event state.
+    pub fn with_state(mut self, state: Option<State>) -> Self {
+        self.state = state;
+        self
+    }
 }
 
 impl From<Key> for Node {
@@ -51,19 +62,41 @@ impl From<Key> for Node {
     }
 }
 
-#[derive(Copy, Clone, Debug, Default, Display, Hash, PartialEq, Eq)]
+/// The state of a key event, written as a prefix (`release-space`) or as a
+/// suffix (`space:up`).
+#[derive(Copy, Clone, Debug, Default, Display, Hash, PartialEq, Eq, EnumString, AsRefStr)]
 pub enum State {
-    /// The key is pressed.
+    /// The key is pressed (`press-`, `:down`).
     #[default]
+    #[strum(serialize = "press")]
     Pressed,
-    /// The key is released.
+    /// The key is released (`release-`, `:up`).
+    #[strum(serialize = "release")]
     Released,
-    /// The key is held down.
+    /// The key is held down (`hold-`).
+    #[strum(serialize = "hold")]
     Held,
-    /// The key is repeated.
+    /// The key is repeated (`repeat-`).
+    #[strum(serialize = "repeat")]
     Repeated,
 }
 
+impl State {
+    /// Returns `true` if a pattern with state `self` matches an input key
+    /// event with state `input`.
+    ///
+    /// A pattern without a state matches any event but a release, and an input
+    /// without a state (e.g. from a backend that does not report it) counts as
+    /// a press.
+    pub fn accepts(pattern: Option<State>, input: Option<State>) -> bool {
+        match (pattern, input) {
+            (None, input) => input != Some(State::Released),
+            (Some(pattern), None) => pattern == State::Pressed,
+            (Some(pattern), Some(input)) => pattern == input,
+        }
+    }
+}
+
 /// Modifier keys that can be combined with other keys.
 ///
 /// Each variant is represented as a bitflag.
@@ -104,8 +137,11 @@ pub(crate) const MODIFIERS: [Modifier; 4] = [
 
 /// Supported keyboard key types for input nodes.
 ///
-/// This enum includes character keys, function keys, and special keys.
-#[derive(Clone, Debug, Display, PartialEq, Eq, Hash, EnumString, AsRefStr)]
+/// This enum includes character keys, function keys, special keys, lock and
+/// system keys, keypad keys and media keys.
+#[derive(
+    Clone, Debug, Display, PartialEq, Eq, Hash, EnumString, AsRefStr, EnumIter, EnumMessage,
+)]
 #[strum(serialize_all = "lowercase")]
 pub enum Key {
     /// Shift+Tab / Back tab.
@@ -141,7 +177,64 @@ pub enum Key {
     Tab,
     /// Up arrow key.
     Up,
-    /// Function key (e.g., F1-F12).
+    /// Caps Lock key.
+    CapsLock,
+    /// Scroll Lock key.
+    ScrollLock,
+    /// Num Lock key.
+    NumLock,
+    /// Print Screen key.
+    PrintScreen,
+    /// Pause/Break key.
+    Pause,
+    /// Menu (context menu) key.
+    Menu,
+    /// Keypad digit key (e.g., kp0-kp9).
+    #[strum(disabled)]
+    Keypad(u8),
+    /// Keypad Enter key.
+    #[strum(serialize = "kpenter")]
+    KeypadEnter,
+    /// Keypad `+` key.
+    #[strum(serialize = "kpplus")]
+    KeypadPlus,
+    /// Keypad `-` key.
+    #[strum(serialize = "kpminus")]
+    KeypadMinus,
+    /// Keypad `*` key.
+    #[strum(serialize = "kpmultiply")]
+    KeypadMultiply,
+    /// Keypad `/` key.
+    #[strum(serialize = "kpdivide")]
+    KeypadDivide,
+    /// Keypad decimal point key.
+    #[strum(serialize = "kpdecimal")]
+    KeypadDecimal,
+    /// Media play key.
+    Play,
+    /// Media pause key.
+    MediaPause,
+    /// Media play/pause key.
+    PlayPause,
+    /// Media stop key.
+    Stop,
+    /// Media fast forward key.
+    FastForward,
+    /// Media rewind key.
+    Rewind,
+    /// Media next track key.
+    NextTrack,
+    /// Media previous track key.
+    PrevTrack,
+    /// Media record key.
+    Record,
+    /// Volume up key.
+    VolumeUp,
+    /// Volume down key.
+    VolumeDown,
+    /// Volume mute key.
+    Mute,
+    /// Function key (e.g., F1-F35).
     F(u8),
     /// A Unicode character key.
     Char(char),
@@ -150,6 +243,39 @@ pub enum Key {
     Group(CharGroup),
 }
 
+impl Key {
+    /// Returns `true` for the keys of the numeric keypad (e.g. `kp5` or `kpenter`).
+    pub fn is_keypad(&self) -> bool {
+        matches!(
+            self,
+            Key::Keypad(_)
+                | Key::KeypadEnter
+                | Key::KeypadPlus
+                | Key::KeypadMinus
+                | Key::KeypadMultiply
+                | Key::KeypadDivide
+                | Key::KeypadDecimal
+        )
+    }
+
+    /// Returns the key of the main keyboard that a keypad key stands for (e.g.
+    /// `5` for `kp5` or `enter` for `kpenter`), or `None` for other keys.
+    pub fn main_key(&self) -> Option<Key> {
+        let key = match self {
+            Key::Keypad(n) => Key::Char(char::from_digit((*n).into(), 10)?),
+            Key::KeypadEnter => Key::Enter,
+            Key::KeypadPlus => Key::Char('+'),
+            Key::KeypadMinus => Key::Char('-'),
+            Key::KeypadMultiply => Key::Char('*'),
+            Key::KeypadDivide => Key::Char('/'),
+            Key::KeypadDecimal => Key::Char('.'),
+            _ => return None,
+        };
+
+        Some(key)
+    }
+}
+
 /// Character group types for pattern matching
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, EnumString)]
 #[strum(serialize_all = "lowercase")]
@@ -164,6 +290,16 @@ pub enum CharGroup {
     Alpha,
     /// Matches alphanumeric ASCII characters (a-z, A-Z, 0-9)
     Alnum,
+    /// Matches Unicode numeric characters (e.g. 0-9, ٣, ½)
+    Numeric,
+    /// Matches lowercase Unicode letters (e.g. a-z, é, ж)
+    Lowercase,
+    /// Matches uppercase Unicode letters (e.g. A-Z, É, Ж)
+    Uppercase,
+    /// Matches Unicode letters (e.g. a-z, é, ж, 日)
+    Alphabetic,
+    /// Matches Unicode letters and numeric characters
+    Alphanumeric,
     /// Matches any character
     Any,
 }
@@ -177,6 +313,11 @@ impl CharGroup {
             CharGroup::Upper => c.is_ascii_uppercase(),
             CharGroup::Alpha => c.is_ascii_alphabetic(),
             CharGroup::Alnum => c.is_ascii_alphanumeric(),
+            CharGroup::Numeric => c.is_numeric(),
+            CharGroup::Lowercase => c.is_lowercase(),
+            CharGroup::Uppercase => c.is_uppercase(),
+            CharGroup::Alphabetic => c.is_alphabetic(),
+            CharGroup::Alphanumeric => c.is_alphanumeric(),
             CharGroup::Any => true,
         }
     }
@@ -190,6 +331,11 @@ impl std::fmt::Display for CharGroup {
             Self::Upper => "upper",
             Self::Alpha => "alpha",
             Self::Alnum => "alnum",
+            Self::Numeric => "numeric",
+            Self::Lowercase => "lowercase",
+            Self::Uppercase => "uppercase",
+            Self::Alphabetic => "alphabetic",
+            Self::Alphanumeric => "alphanumeric",
             Self::Any => "any",
         };
         write!(f, "@{name}")
@@ -209,9 +355,25 @@ impl<'s> Deserialize<'s> for Node {
     }
 }
 
+/// Custom serialization for [`Node`] into its string form (see [`Display`]).
+///
+/// The output can be parsed back by the [`Deserialize`] implementation.
+impl Serialize for Node {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
 impl Display for Node {
     /// Formats the node as a human-readable string (e.g., "ctrl-shift-a", "alt-f4").
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if let Some(state) = self.state {
+            write!(f, "{state}{KEY_SEP}")?;
+        }
+
         for m in &MODIFIERS {
             if self.modifiers & *m as u8 != 0 {
                 write!(f, "{m}{KEY_SEP}").unwrap();
@@ -221,6 +383,7 @@ impl Display for Node {
         match self.key {
             Key::Char(char) => write!(f, "{char}"),
             Key::F(n) => write!(f, "{}{n}", self.key),
+            Key::Keypad(n) => write!(f, "kp{n}"),
             Key::Group(n) => write!(f, "{n}"),
             _ => write!(f, "{}", self.key),
         }
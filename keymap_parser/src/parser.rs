@@ -7,21 +7,30 @@
 //! ## Supported Syntax
 //!
 //! ```text
-//! node      = modifiers* key
+//! node      = (modifiers | states)* key (":" suffix)?
 //! modifiers = modifier "-"
 //! modifier  = "ctrl" | "cmd" | "alt" | "shift"
-//! key       = fn-key | named-key | group | char
-//! fn-key    = "f" digit digit?
+//! states    = state "-"
+//! state     = "press" | "release" | "repeat" | "hold"
+//! suffix    = state | "down" | "up"
+//! key       = fn-key | kp-key | named-key | group | char
+//! fn-key    = "f" digit digit?   (f0 - f35)
+//! kp-key    = "kp" digit
 //! named-key = "del" | "insert" | "end" | ...
-//! group     = "@" ("digit" | "lower" | "upper" | "alnum" | "alpha" | "any")
-//! char      = ascii-char
+//! group     = "@" ("digit" | "lower" | "upper" | "alnum" | "alpha" | "numeric" | "lowercase"
+//!                  | "uppercase" | "alphabetic" | "alphanumeric" | "any")
+//! char      = unicode-scalar
 //! ```
 //!
-//! Each `Node` consists of optional modifier keys followed by a key identifier.
+//! Each `Node` consists of optional modifier keys followed by a key identifier. A key state,
+//! written as a prefix (`release-space`) or a suffix (`space:up`), restricts the node to key
+//! events in that state.
 
 use std::str::FromStr;
 
-use crate::node::{CharGroup, Key, Modifier, Node, KEY_SEP};
+use strum::{EnumMessage, IntoEnumIterator};
+
+use crate::node::{CharGroup, Key, Modifier, Node, State, KEY_SEP, MODIFIERS, STATE_SEP};
 
 type ParserFn<T> = fn(&mut Parser) -> Result<Option<T>, ParseError>;
 
@@ -190,22 +199,73 @@ pub fn parse(s: &str) -> Result<Node, ParseError> {
     Ok(node)
 }
 
-/// Parses a key combination with optional modifiers followed by a key.
+/// Parses a key combination with an optional state and modifiers followed by
+/// a key and an optional state suffix.
 ///
-/// Grammar: `node = modifiers* key`
+/// Grammar: `node = modifier{0,4} (state modifier{0,4})? key (":" state-suffix)?`
 fn parse_node(parser: &mut Parser) -> Result<Node, ParseError> {
     let mut modifiers = 0u8;
-
-    for _ in 0..4 {
-        if let Some(modifier) = try_parse_modifier(parser)? {
-            modifiers |= modifier as u8;
-        } else {
-            break;
+    let mut state = None;
+
+    // Up to four modifiers on each side of a state prefix, as in `pattern`
+    let mut count = 0;
+    loop {
+        if count < 4 {
+            if let Some(modifier) = try_parse_modifier(parser)? {
+                modifiers |= modifier as u8;
+                count += 1;
+                continue;
+            }
+        }
+        match try_parse_state(parser)? {
+            Some(prefix) if state.is_some() => {
+                return Err(parser.error(format!("duplicate key state: '{prefix}'")));
+            }
+            Some(prefix) => {
+                state = Some(prefix);
+                count = 0;
+            }
+            None => break,
         }
     }
 
     let key = parse_key(parser)?;
-    Ok(Node::new(modifiers, key))
+    if let Some(suffix) = try_parse_state_suffix(parser)? {
+        if state.is_some() {
+            return Err(parser.error(format!("duplicate key state: '{suffix}'")));
+        }
+        state = Some(suffix);
+    }
+
+    Ok(Node::new(modifiers, key).with_state(state))
+}
+
+/// Attempts to parse a key state prefix such as `release-`.
+fn try_parse_state(parser: &mut Parser) -> Result<Option<State>, ParseError> {
+    parser.try_parse(|p| {
+        let name = p.take_while(|ch| ch.is_ascii_alphabetic());
+        let Ok(state) = name.parse::<State>() else {
+            return Ok(None);
+        };
+
+        p.take(KEY_SEP)?;
+
+        Ok(Some(state))
+    })
+}
+
+/// Attempts to parse a key state suffix such as `:up` or `:release`.
+fn try_parse_state_suffix(parser: &mut Parser) -> Result<Option<State>, ParseError> {
+    parser.try_parse(|p| {
+        p.take(STATE_SEP)?;
+
+        let name = p.take_while(|ch| ch.is_ascii_alphabetic());
+        Ok(match name.as_str() {
+            "down" => Some(State::Pressed),
+            "up" => Some(State::Released),
+            name => name.parse().ok(),
+        })
+    })
 }
 
 /// Attempts to parse a single modifier, followed by a `-`.
@@ -224,10 +284,11 @@ fn try_parse_modifier(parser: &mut Parser) -> Result<Option<Modifier>, ParseErro
     })
 }
 
-/// Parses a key value, which may be a function key, named key, character group, or ASCII char.
+/// Parses a key value, which may be a function key, named key, character group, or char.
 fn parse_key(parser: &mut Parser) -> Result<Key, ParseError> {
     match parser.alt(&[
         try_parse_fn_key,
+        try_parse_keypad_key,
         try_parse_named_key,
         try_parse_group,
         try_parse_char,
@@ -237,20 +298,40 @@ fn parse_key(parser: &mut Parser) -> Result<Key, ParseError> {
     }
 }
 
-/// Attempts to parse a function key (e.g., `"f1"` to `"f12"`).
+/// Attempts to parse a function key (e.g., `"f1"` to `"f35"`).
 fn try_parse_fn_key(parser: &mut Parser) -> Result<Option<Key>, ParseError> {
-    if parser.peek() != Some('f') || parser.peek_at(1).is_none() {
+    if parser.peek() != Some('f') || !parser.peek_at(1).is_some_and(|ch| ch.is_ascii_digit()) {
         return Ok(None);
     }
 
+    let position = parser.position;
     parser.take('f')?;
-    parser.try_parse(|p| {
-        let num = p.take_while(|ch| ch.is_ascii_digit());
-        match num.parse::<u8>() {
-            Ok(n) if n <= 12 => Ok(Some(Key::F(n))),
-            _ => Err(p.error("invalid function key number (must be 0-12)".to_string())),
+
+    let num = parser.take_while(|ch| ch.is_ascii_digit());
+    match num.parse::<u8>() {
+        Ok(n) if n <= MAX_FN_KEY => Ok(Some(Key::F(n))),
+        _ => Err(ParseError {
+            message: format!("function key out of range: 'f{num}' (expected f0-f{MAX_FN_KEY})"),
+            position,
+        }),
+    }
+}
+
+/// Attempts to parse a keypad digit key (e.g., `"kp0"` to `"kp9"`).
+fn try_parse_keypad_key(parser: &mut Parser) -> Result<Option<Key>, ParseError> {
+    if !parser.input.starts_with("kp") {
+        return Ok(None);
+    }
+
+    match parser.peek_at(2).and_then(|ch| ch.to_digit(10)) {
+        Some(n) => {
+            parser.take('k')?;
+            parser.take('p')?;
+            parser.next();
+            Ok(Some(Key::Keypad(n as u8)))
         }
-    })
+        None => Ok(None),
+    }
 }
 
 /// Attempts to parse a named key such as `"del"`, `"insert"`, or `"end"`.
@@ -285,10 +366,11 @@ fn try_parse_group(parser: &mut Parser) -> Result<Option<Key>, ParseError> {
     Ok(Some(group))
 }
 
-/// Attempts to parse a single ASCII character as a key.
+/// Attempts to parse a single character (any Unicode scalar value other than
+/// non-ASCII whitespace and control characters) as a key.
 fn try_parse_char(parser: &mut Parser) -> Result<Option<Key>, ParseError> {
     if let Some(ch) = parser.peek() {
-        if ch.is_ascii() {
+        if ch.is_ascii() || !(ch.is_whitespace() || ch.is_control()) {
             parser.next();
             Ok(Some(Key::Char(ch)))
         } else {
@@ -322,6 +404,83 @@ pub fn parse_seq(s: &str) -> Result<Vec<Node>, ParseError> {
     str::split_whitespace(s).map(parse).collect()
 }
 
+/// Returns the named keys accepted by [`parse`] (e.g. `"esc"` and its
+/// aliases), as written in key expressions.
+///
+/// # Examples
+///
+/// ```
+/// let names = keymap_parser::parser::named_keys();
+/// assert!(names.contains(&"del") && names.contains(&"delete"));
+/// ```
+pub fn named_keys() -> Vec<&'static str> {
+    // Function keys and chars are parsed separately, keypad digits and groups
+    // are disabled
+    Key::iter()
+        .filter(|key| !matches!(key, Key::F(_) | Key::Char(_)))
+        .flat_map(|key| key.get_serializations().iter().copied())
+        .collect()
+}
+
+/// Key states accepted by [`parse`] as a prefix (`"release-"`) or a suffix
+/// (`":release"`). The suffix also accepts `":down"` and `":up"`.
+pub const STATES: &[&str] = &["press", "release", "repeat", "hold"];
+
+/// The highest function key number accepted by [`parse`] (`"f35"`).
+pub const MAX_FN_KEY: u8 = 35;
+
+/// Character groups accepted by [`parse`], without the leading `@`.
+pub const CHAR_GROUPS: &[&str] = &[
+    "digit",
+    "lower",
+    "upper",
+    "alpha",
+    "alnum",
+    "numeric",
+    "lowercase",
+    "uppercase",
+    "alphabetic",
+    "alphanumeric",
+    "any",
+];
+
+/// Returns a regular expression that matches the key sequences accepted by
+/// [`parse_seq`], e.g. `"ctrl-b n"`.
+///
+/// The expression only uses syntax shared by common regex engines (including
+/// the ECMA 262 dialect of JSON Schema), so it can be used to validate keys
+/// outside of Rust.
+///
+/// # Examples
+///
+/// ```
+/// let pattern = keymap_parser::parser::pattern();
+/// assert!(pattern.starts_with('^') && pattern.ends_with('$'));
+/// ```
+pub fn pattern() -> String {
+    // Any char but whitespace and the non-ASCII control chars, as in `try_parse_char`
+    const CHAR: &str = r"[^\s\u0080-\u009f]";
+
+    let modifiers = MODIFIERS
+        .iter()
+        .map(|modifier| modifier.as_ref())
+        .collect::<Vec<_>>()
+        .join("|");
+    let states = STATES.join("|");
+    let modifiers = format!("(?:(?:{modifiers}){KEY_SEP}){{0,4}}");
+    let key = format!(
+        "(?:f(?:3[0-5]|[12][0-9]|[0-9])|kp[0-9]|{}|@(?:{})|{CHAR})",
+        named_keys().join("|"),
+        CHAR_GROUPS.join("|"),
+    );
+    // A state is either a prefix or a suffix, but not both
+    let node = format!(
+        "(?:{modifiers}(?:{states}){KEY_SEP}{modifiers}{key}|{modifiers}{key}(?:{STATE_SEP}(?:{states}|down|up))?)"
+    );
+
+    format!(r"^\s*{node}(?:\s+{node})*\s*$")
+}
+
 impl FromStr for Node {
     type Err = ParseError;
 
@@ -333,10 +492,11 @@ impl FromStr for Node {
 #[cfg(test)]
 mod tests {
     use serde::Deserialize;
+    use strum::IntoEnumIterator;
 
-    use crate::parser::{CharGroup, Key, Modifier, Node};
+    use crate::parser::{CharGroup, Key, Modifier, Node, State};
 
-    use super::{parse, ParseError};
+    use super::{parse, ParseError, MAX_FN_KEY};
 
     #[test]
     fn test_parse() {
@@ -382,19 +542,108 @@ mod tests {
 
     #[test]
     fn test_parse_fn_key() {
-        // Valid function key numbers: f0 - f12
-        (0..=12).for_each(|n| {
+        // Valid function key numbers: f0 - f35
+        (0..=MAX_FN_KEY).for_each(|n| {
             let input = format!("f{n}");
-            let result = parse(&input);
-            assert_eq!(Key::F(n), result.unwrap().key);
+            let node = parse(&input).unwrap();
+            assert_eq!(Key::F(n), node.key);
+
+            // Round trip through Display
+            assert_eq!(node.to_string(), input);
         });
 
-        // Invalid: above f12
-        [13, 15].iter().for_each(|n| {
-            let input = format!("f{n}");
-            let result = parse(&input);
-            assert!(result.is_err());
+        // Invalid: above f35
+        [36, 99, 256].iter().for_each(|n| {
+            let input = format!("ctrl-f{n}");
+            assert_eq!(
+                parse(&input),
+                Err(ParseError {
+                    message: format!("function key out of range: 'f{n}' (expected f0-f35)"),
+                    position: 5,
+                })
+            );
         });
+
+        // Not a function key
+        assert!(parse("fa").is_err());
+        assert_eq!(parse("f").unwrap().key, Key::Char('f'));
+    }
+
+    #[test]
+    fn test_parse_state() {
+        let space = |state| Node::from(Key::Space).with_state(Some(state));
+        [
+            ("release-space", space(State::Released)),
+            ("space:up", space(State::Released)),
+            ("space:release", space(State::Released)),
+            ("press-space", space(State::Pressed)),
+            ("space:down", space(State::Pressed)),
+            ("repeat-space", space(State::Repeated)),
+            ("space:hold", space(State::Held)),
+            (
+                "ctrl-release-a",
+                Node::new(Modifier::Ctrl as u8, Key::Char('a')).with_state(Some(State::Released)),
+            ),
+            (
+                "::up",
+                Node::from(Key::Char(':')).with_state(Some(State::Released)),
+            ),
+        ]
+        .iter()
+        .for_each(|(input, expected)| {
+            assert_eq!(&parse(input).unwrap(), expected, "{input}");
+        });
+
+        // The prefix form is canonical
+        assert_eq!(parse("ctrl-a:up").unwrap().to_string(), "release-ctrl-a");
+        assert_eq!(parse("a").unwrap().state, None);
+        assert_eq!(parse(":").unwrap().key, Key::Char(':'));
+
+        ["release-a:up", "press-repeat-a", "a:b", "release-"]
+            .iter()
+            .for_each(|input| assert!(parse(input).is_err(), "{input}"));
+    }
+
+    #[test]
+    fn test_state_accepts() {
+        use State::*;
+
+        [
+            (None, None, true),
+            (None, Some(Pressed), true),
+            (None, Some(Repeated), true),
+            (None, Some(Released), false),
+            (Some(Pressed), None, true),
+            (Some(Pressed), Some(Repeated), false),
+            (Some(Released), None, false),
+            (Some(Released), Some(Released), true),
+        ]
+        .iter()
+        .for_each(|(pattern, input, expected)| {
+            assert_eq!(
+                State::accepts(*pattern, *input),
+                *expected,
+                "{pattern:?} {input:?}"
+            );
+        });
+    }
+
+    #[test]
+    fn test_parse_keypad_key() {
+        (0..=9).for_each(|n| {
+            let input = format!("kp{n}");
+            let node = parse(&input).unwrap();
+            assert_eq!(Key::Keypad(n), node.key);
+            assert_eq!(node.to_string(), input);
+        });
+
+        assert_eq!(
+            parse("ctrl-kpplus").unwrap(),
+            Node::new(Modifier::Ctrl as u8, Key::KeypadPlus)
+        );
+        assert!(parse("kp").is_err());
+        assert!(parse("kp10").is_err());
+        assert!(parse("kpa").is_err());
     }
 
     #[test]
@@ -408,6 +657,153 @@ mod tests {
             });
     }
 
+    #[test]
+    fn test_named_keys() {
+        super::named_keys().iter().for_each(|name| {
+            let node = parse(name).unwrap();
+            assert!(
+                !matches!(
+                    node.key,
+                    Key::Char(_) | Key::F(_) | Key::Keypad(_) | Key::Group(_)
+                ),
+                "{name}"
+            );
+
+            // Every name but the "del" alias is displayed as written
+            if *name != "del" {
+                assert_eq!(&node.to_string(), name);
+            }
+        });
+        super::CHAR_GROUPS.iter().for_each(|name| {
+            assert!(parse(&format!("@{name}")).is_ok(), "{name}");
+        });
+    }
+
+    #[test]
+    fn test_pattern() {
+        let pattern = regex::Regex::new(&super::pattern()).unwrap();
+
+        [
+            "a",
+            "ctrl-b n",
+            "shift-cmd-del",
+            "alt-@lower",
+            "f0",
+            "f12",
+            "-",
+            "ctrl--",
+            "g g",
+            "ctrl-alt-cmd-shift-space",
+            "alt-f",
+            "delta",
+            "f13",
+            "f24",
+            "f35",
+            "f36",
+            "fa",
+            "kp5",
+            "ctrl-kpenter",
+            "kp",
+            "kp10",
+            "shift-volumeup",
+            "release-space",
+            "ctrl-release-a",
+            "space:up",
+            "::down",
+            "a:b",
+            "release-a:up",
+            "press-repeat-a",
+            "shift-a-delete",
+            "ctrl-",
+            "shift",
+            "@invalid",
+            "",
+            "ctrl-b -l",
+            "é",
+            "ctrl-ж g",
+            "@alphabetic",
+            "@alphabeticx",
+            "\u{1}",
+            "\u{90}",
+            "ctrl-\u{9f}",
+            // At most four modifiers on each side of a state prefix
+            "ctrl-alt-shift-release-cmd-ctrl-a",
+            "ctrl-alt-shift-cmd-release-ctrl-alt-shift-cmd-a",
+            "ctrl-alt-shift-cmd-ctrl-a",
+            "release-ctrl-alt-shift-cmd-ctrl-a",
+            "ctrl-alt-shift-cmd-a:up",
+            "ctrl-release-alt-a:up",
+        ]
+        .iter()
+        .for_each(|input| {
+            assert_eq!(
+                pattern.is_match(input),
+                super::parse_seq(input).is_ok_and(|seq| !seq.is_empty()),
+                "{input}"
+            );
+        });
+
+        // Every key matches as displayed
+        Key::iter()
+            .chain([Key::Keypad(5), Key::Group(CharGroup::Digit)])
+            .for_each(|key| {
+                let input = Node::from(key.clone()).to_string();
+                assert!(pattern.is_match(&input), "{input}");
+                assert_eq!(parse(&input).unwrap().key, key, "{input}");
+            });
+    }
+
+    #[test]
+    fn test_parse_unicode() {
+        ["é", "ö", "ß", "ж", "Ж", "日", "😀"]
+            .iter()
+            .for_each(|input| {
+                let node = parse(input).unwrap();
+                assert_eq!(node.key, Key::Char(input.chars().next().unwrap()));
+
+                // Round trip through Display
+                assert_eq!(&node.to_string(), input);
+                assert_eq!(parse(&node.to_string()).unwrap(), node);
+            });
+
+        assert_eq!(
+            parse("alt-ö").unwrap(),
+            Node::new(Modifier::Alt as u8, Key::Char('ö'))
+        );
+        assert_eq!(
+            super::parse_seq("ctrl-x ж").unwrap(),
+            vec![
+                Node::new(Modifier::Ctrl as u8, Key::Char('x')),
+                Node::from(Key::Char('ж'))
+            ]
+        );
+
+        // Only a single character is a key
+        assert!(parse("éé").is_err());
+        assert!(parse("\u{a0}").is_err());
+    }
+
+    #[test]
+    fn test_char_group_matches() {
+        [
+            (CharGroup::Alpha, 'é', false),
+            (CharGroup::Alphabetic, 'é', true),
+            (CharGroup::Alphabetic, 'ж', true),
+            (CharGroup::Alphabetic, '1', false),
+            (CharGroup::Lowercase, 'ö', true),
+            (CharGroup::Lowercase, 'Ö', false),
+            (CharGroup::Uppercase, 'Ж', true),
+            (CharGroup::Numeric, '٣', true),
+            (CharGroup::Digit, '٣', false),
+            (CharGroup::Alphanumeric, '日', true),
+            (CharGroup::Alphanumeric, '-', false),
+        ]
+        .iter()
+        .for_each(|(group, ch, expected)| {
+            assert_eq!(group.matches(*ch), *expected, "{group} {ch}");
+        });
+    }
+
     #[test]
     fn test_parse_char_groups() {
         [
@@ -416,6 +812,11 @@ mod tests {
             ("@upper", Key::Group(CharGroup::Upper)),
             ("@alpha", Key::Group(CharGroup::Alpha)),
             ("@alnum", Key::Group(CharGroup::Alnum)),
+            ("@numeric", Key::Group(CharGroup::Numeric)),
+            ("@lowercase", Key::Group(CharGroup::Lowercase)),
+            ("@uppercase", Key::Group(CharGroup::Uppercase)),
+            ("@alphabetic", Key::Group(CharGroup::Alphabetic)),
+            ("@alphanumeric", Key::Group(CharGroup::Alphanumeric)),
             ("@any", Key::Group(CharGroup::Any)),
         ]
         .iter()
@@ -503,6 +904,31 @@ delete = "d"
         });
     }
 
+    #[test]
+    fn test_serialize() {
+        use std::collections::BTreeMap;
+
+        #[derive(serde::Serialize)]
+        struct Test {
+            keys: BTreeMap<String, Vec<Node>>,
+        }
+
+        let keys = BTreeMap::from([(
+            "a".to_string(),
+            vec![
+                parse("shift-cmd-del").unwrap(),
+                parse("alt-@lower").unwrap(),
+                parse("f1").unwrap(),
+            ],
+        )]);
+
+        let output = toml::to_string(&Test { keys }).unwrap();
+        assert_eq!(
+            output,
+            "[keys]\na = [\"cmd-shift-delete\", \"alt-@lower\", \"f1\"]\n"
+        );
+    }
+
     #[test]
     fn test_parse_str() {
         [
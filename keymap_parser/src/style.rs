@@ -0,0 +1,574 @@
+//! # Display Styles
+//!
+//! The [`Display`] implementation of [`Node`] prints key expressions such as
+//! `cmd-shift-f`, which round-trip through the parser. For help screens,
+//! [`Style`] formats keys the way users of each platform or editor expect:
+//!
+//! | Style              | `ctrl-shift-f` | `ctrl-x ctrl-s`   |
+//! | ------------------ | -------------- | ----------------- |
+//! | [`Style::Plain`]   | `ctrl-shift-f` | `ctrl-x ctrl-s`   |
+//! | [`Style::Mac`]     | `⌃⇧F`          | `⌃X ⌃S`           |
+//! | [`Style::Windows`] | `Ctrl+Shift+F` | `Ctrl+X Ctrl+S`   |
+//! | [`Style::Linux`]   | `Ctrl+Shift+F` | `Ctrl+X Ctrl+S`   |
+//! | [`Style::Emacs`]   | `C-S-f`        | `C-x C-s`         |
+//! | [`Style::Vim`]     | `<C-S-f>`      | `<C-x><C-s>`      |
+//! | [`Style::Symbol`]  | `^⇧f`          | `^x ^s`           |
+//!
+//! # Examples
+//!
+//! ```
+//! use keymap_parser::{parse_seq, style::{Style, StyledDisplay}};
+//!
+//! let keys = parse_seq("cmd-shift-f").unwrap();
+//! assert_eq!(keys.styled(Style::Mac).to_string(), "⇧⌘F");
+//! assert_eq!(keys.styled(Style::Windows).to_string(), "Shift+Win+F");
+//! ```
+//!
+//! A key state follows the key in parentheses, e.g. `Space (Release)` for
+//! `release-space`, or `<Space (release)>` in [`Style::Vim`].
+use std::fmt::{self, Display, Formatter};
+
+use crate::node::{Key, Modifier, Node, State};
+
+/// A convention for displaying keys to end users.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Style {
+    /// Key expressions as parsed, e.g. `ctrl-shift-f`.
+    #[default]
+    Plain,
+    /// macOS menu glyphs, e.g. `⌃⇧F`.
+    Mac,
+    /// Windows names, e.g. `Ctrl+Shift+F` and `Win+E`.
+    Windows,
+    /// Linux names, e.g. `Ctrl+Shift+F` and `Super+E`.
+    Linux,
+    /// Emacs notation, e.g. `C-S-f` and `RET`.
+    Emacs,
+    /// Vim notation, e.g. `<C-S-f>` and `<CR>`.
+    ///
+    /// Vim cannot map lock, system and media keys, so these have no Vim
+    /// notation and are shown by their key expression names, e.g.
+    /// `<capslock>` or `<playpause>`.
+    Vim,
+    /// Compact glyphs for narrow columns, e.g. `^⇧f` and `␣`.
+    Symbol,
+}
+
+impl Style {
+    /// Returns the style of an operating system, as named by
+    /// [`std::env::consts::OS`] (e.g. `"macos"`, `"windows"` or `"linux"`).
+    ///
+    /// Apple platforms use [`Style::Mac`], Windows uses [`Style::Windows`] and
+    /// any other system [`Style::Linux`].
+    pub fn from_os(os: &str) -> Self {
+        match os {
+            "macos" | "ios" => Style::Mac,
+            "windows" => Style::Windows,
+            _ => Style::Linux,
+        }
+    }
+
+    /// Returns the style of the operating system the program runs on.
+    ///
+    /// On the web (`wasm32-unknown-unknown`) the OS is unknown, so detect the
+    /// platform of the browser and use [`Style::from_os`] instead.
+    pub fn native() -> Self {
+        Self::from_os(std::env::consts::OS)
+    }
+
+    /// The modifiers in display order, with their names.
+    fn modifiers(self) -> [(Modifier, &'static str); 4] {
+        match self {
+            Style::Plain => unreachable!("plain keys are displayed as parsed"),
+            Style::Mac => [
+                (Modifier::Ctrl, "⌃"),
+                (Modifier::Alt, "⌥"),
+                (Modifier::Shift, "⇧"),
+                (Modifier::Cmd, "⌘"),
+            ],
+            Style::Windows => [
+                (Modifier::Ctrl, "Ctrl+"),
+                (Modifier::Alt, "Alt+"),
+                (Modifier::Shift, "Shift+"),
+                (Modifier::Cmd, "Win+"),
+            ],
+            Style::Linux => [
+                (Modifier::Ctrl, "Ctrl+"),
+                (Modifier::Alt, "Alt+"),
+                (Modifier::Shift, "Shift+"),
+                (Modifier::Cmd, "Super+"),
+            ],
+            Style::Emacs => [
+                (Modifier::Ctrl, "C-"),
+                (Modifier::Alt, "M-"),
+                (Modifier::Shift, "S-"),
+                (Modifier::Cmd, "s-"),
+            ],
+            Style::Vim => [
+                (Modifier::Ctrl, "C-"),
+                (Modifier::Alt, "M-"),
+                (Modifier::Shift, "S-"),
+                (Modifier::Cmd, "D-"),
+            ],
+            Style::Symbol => [
+                (Modifier::Ctrl, "^"),
+                (Modifier::Alt, "⌥"),
+                (Modifier::Shift, "⇧"),
+                (Modifier::Cmd, "⌘"),
+            ],
+        }
+    }
+
+    /// Whether letters are shown as key caps (`F`, with `Shift` for `F`)
+    /// rather than as typed (`f` and `F`).
+    fn key_caps(self) -> bool {
+        matches!(self, Style::Mac | Style::Windows | Style::Linux)
+    }
+
+    /// The name of a named key, or `None` for characters and groups.
+    fn key_name(self, key: &Key) -> Option<&'static str> {
+        use Style::*;
+
+        Some(match (self, key) {
+            (Mac | Symbol, Key::BackTab) => "⇤",
+            (Windows | Linux, Key::BackTab) => "Shift+Tab",
+            (Emacs, Key::BackTab) => "<backtab>",
+            (Vim, Key::BackTab) => "S-Tab",
+            (Mac | Symbol, Key::Backspace) => "⌫",
+            (Windows | Linux, Key::Backspace) => "Backspace",
+            (Emacs, Key::Backspace) => "DEL",
+            (Vim, Key::Backspace) => "BS",
+            (Mac | Symbol, Key::Delete) => "⌦",
+            (Windows | Linux, Key::Delete) => "Delete",
+            (Emacs, Key::Delete) => "<delete>",
+            (Vim, Key::Delete) => "Del",
+            (Mac | Symbol, Key::Down) => "↓",
+            (Emacs, Key::Down) => "<down>",
+            (_, Key::Down) => "Down",
+            (Mac | Symbol, Key::End) => "↘",
+            (Emacs, Key::End) => "<end>",
+            (_, Key::End) => "End",
+            (Mac | Symbol, Key::Enter) => "↩",
+            (Emacs, Key::Enter) => "RET",
+            (Vim, Key::Enter) => "CR",
+            (_, Key::Enter) => "Enter",
+            (Mac | Symbol, Key::Esc) => "⎋",
+            (Emacs, Key::Esc) => "ESC",
+            (_, Key::Esc) => "Esc",
+            (Mac | Symbol, Key::Home) => "↖",
+            (Emacs, Key::Home) => "<home>",
+            (_, Key::Home) => "Home",
+            (Emacs, Key::Insert) => "<insert>",
+            (_, Key::Insert) => "Insert",
+            (Mac | Symbol, Key::Left) => "←",
+            (Emacs, Key::Left) => "<left>",
+            (_, Key::Left) => "Left",
+            (Mac | Symbol, Key::PageDown) => "⇟",
+            (Emacs, Key::PageDown) => "<next>",
+            (_, Key::PageDown) => "PageDown",
+            (Mac | Symbol, Key::PageUp) => "⇞",
+            (Emacs, Key::PageUp) => "<prior>",
+            (_, Key::PageUp) => "PageUp",
+            (Mac | Symbol, Key::Right) => "→",
+            (Emacs, Key::Right) => "<right>",
+            (_, Key::Right) => "Right",
+            (Symbol, Key::Space) => "␣",
+            (Emacs, Key::Space) => "SPC",
+            (_, Key::Space) => "Space",
+            (Mac | Symbol, Key::Tab) => "⇥",
+            (Emacs, Key::Tab) => "TAB",
+            (_, Key::Tab) => "Tab",
+            (Mac | Symbol, Key::Up) => "↑",
+            (Emacs, Key::Up) => "<up>",
+            (_, Key::Up) => "Up",
+            (Mac | Symbol, Key::CapsLock) => "⇪",
+            (Emacs, Key::CapsLock) => "<Caps_Lock>",
+            (Windows | Linux, Key::CapsLock) => "Caps Lock",
+            (Emacs, Key::ScrollLock) => "<Scroll_Lock>",
+            (Windows | Linux | Mac | Symbol, Key::ScrollLock) => "Scroll Lock",
+            (Emacs, Key::NumLock) => "<num_lock>",
+            (Windows | Linux | Mac | Symbol, Key::NumLock) => "Num Lock",
+            (Emacs, Key::PrintScreen) => "<print>",
+            (Windows | Linux | Mac | Symbol, Key::PrintScreen) => "PrtSc",
+            (Emacs, Key::Pause) => "<pause>",
+            (Windows | Linux | Mac | Symbol, Key::Pause) => "Pause",
+            (Emacs, Key::Menu) => "<menu>",
+            (Windows | Linux | Mac | Symbol, Key::Menu) => "Menu",
+            (Emacs, Key::KeypadEnter) => "<kp-enter>",
+            (Vim, Key::KeypadEnter) => "kEnter",
+            (_, Key::KeypadEnter) => "Num Enter",
+            (Emacs, Key::KeypadPlus) => "<kp-add>",
+            (Vim, Key::KeypadPlus) => "kPlus",
+            (_, Key::KeypadPlus) => "Num +",
+            (Emacs, Key::KeypadMinus) => "<kp-subtract>",
+            (Vim, Key::KeypadMinus) => "kMinus",
+            (_, Key::KeypadMinus) => "Num -",
+            (Emacs, Key::KeypadMultiply) => "<kp-multiply>",
+            (Vim, Key::KeypadMultiply) => "kMultiply",
+            (_, Key::KeypadMultiply) => "Num *",
+            (Emacs, Key::KeypadDivide) => "<kp-divide>",
+            (Vim, Key::KeypadDivide) => "kDivide",
+            (_, Key::KeypadDivide) => "Num /",
+            (Emacs, Key::KeypadDecimal) => "<kp-decimal>",
+            (Vim, Key::KeypadDecimal) => "kPoint",
+            (_, Key::KeypadDecimal) => "Num .",
+            (Mac | Symbol, Key::Play) => "▶",
+            (Emacs, Key::Play) => "<XF86AudioPlay>",
+            (Windows | Linux, Key::Play) => "Play",
+            (Mac | Symbol, Key::MediaPause) => "⏸",
+            (Emacs, Key::MediaPause) => "<XF86AudioPause>",
+            (Windows | Linux, Key::MediaPause) => "Media Pause",
+            (Mac | Symbol, Key::PlayPause) => "⏯",
+            // X11 reports this key as XF86AudioPlay, so use the Windows name
+            (Emacs, Key::PlayPause) => "<media-play-pause>",
+            (Windows | Linux, Key::PlayPause) => "Play/Pause",
+            (Mac | Symbol, Key::Stop) => "⏹",
+            (Emacs, Key::Stop) => "<XF86AudioStop>",
+            (Windows | Linux, Key::Stop) => "Stop",
+            (Mac | Symbol, Key::FastForward) => "⏩",
+            (Emacs, Key::FastForward) => "<XF86AudioForward>",
+            (Windows | Linux, Key::FastForward) => "Fast Forward",
+            (Mac | Symbol, Key::Rewind) => "⏪",
+            (Emacs, Key::Rewind) => "<XF86AudioRewind>",
+            (Windows | Linux, Key::Rewind) => "Rewind",
+            (Mac | Symbol, Key::NextTrack) => "⏭",
+            (Emacs, Key::NextTrack) => "<XF86AudioNext>",
+            (Windows | Linux, Key::NextTrack) => "Next Track",
+            (Mac | Symbol, Key::PrevTrack) => "⏮",
+            (Emacs, Key::PrevTrack) => "<XF86AudioPrev>",
+            (Windows | Linux, Key::PrevTrack) => "Previous Track",
+            (Mac | Symbol, Key::Record) => "⏺",
+            (Emacs, Key::Record) => "<XF86AudioRecord>",
+            (Windows | Linux, Key::Record) => "Record",
+            (Mac | Symbol, Key::VolumeUp) => "🔊",
+            (Emacs, Key::VolumeUp) => "<XF86AudioRaiseVolume>",
+            (Windows | Linux, Key::VolumeUp) => "Volume Up",
+            (Mac | Symbol, Key::VolumeDown) => "🔉",
+            (Emacs, Key::VolumeDown) => "<XF86AudioLowerVolume>",
+            (Windows | Linux, Key::VolumeDown) => "Volume Down",
+            (Mac | Symbol, Key::Mute) => "🔇",
+            (Emacs, Key::Mute) => "<XF86AudioMute>",
+            (Windows | Linux, Key::Mute) => "Mute",
+            _ => return None,
+        })
+    }
+
+    /// The name of a key state, e.g. `Release` or `release`.
+    fn state_name(self, state: State) -> &'static str {
+        match (state, self.key_caps()) {
+            (State::Pressed, true) => "Press",
+            (State::Released, true) => "Release",
+            (State::Held, true) => "Hold",
+            (State::Repeated, true) => "Repeat",
+            (State::Pressed, false) => "press",
+            (State::Released, false) => "release",
+            (State::Held, false) => "hold",
+            (State::Repeated, false) => "repeat",
+        }
+    }
+
+    /// The separator between the nodes of a sequence.
+    fn separator(self) -> &'static str {
+        match self {
+            Style::Vim => "",
+            _ => " ",
+        }
+    }
+}
+
+/// A value displayed in a [`Style`], created by [`StyledDisplay::styled`].
+#[derive(Debug, Clone, Copy)]
+pub struct Styled<'a, T: ?Sized> {
+    value: &'a T,
+    style: Style,
+}
+
+/// Formats keys in a [`Style`].
+///
+/// Implemented for [`Node`] and key sequences (`[Node]`).
+pub trait StyledDisplay {
+    /// Returns a wrapper that displays `self` in `style`.
+    fn styled(&self, style: Style) -> Styled<'_, Self>;
+}
+
+impl StyledDisplay for Node {
+    fn styled(&self, style: Style) -> Styled<'_, Self> {
+        Styled { value: self, style }
+    }
+}
+
+impl StyledDisplay for [Node] {
+    fn styled(&self, style: Style) -> Styled<'_, Self> {
+        Styled { value: self, style }
+    }
+}
+
+impl Display for Styled<'_, Node> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let (node, style) = (self.value, self.style);
+        if style == Style::Plain {
+            return write!(f, "{node}");
+        }
+
+        let mut modifiers = node.modifiers;
+        let key = match (&node.key, style.key_name(&node.key)) {
+            (_, Some(name)) => name.to_string(),
+            (Key::F(n), None) if style == Style::Emacs => format!("<f{n}>"),
+            (Key::F(n), None) => format!("F{n}"),
+            (Key::Keypad(n), None) if style == Style::Emacs => format!("<kp-{n}>"),
+            (Key::Keypad(n), None) if style == Style::Vim => format!("k{n}"),
+            (Key::Keypad(n), None) => format!("Num {n}"),
+            (Key::Char(ch), None) if style.key_caps() => {
+                if ch.is_uppercase() {
+                    modifiers |= Modifier::Shift as u8;
+                }
+                // Keep chars without a single uppercase form (e.g. ß)
+                let mut upper = ch.to_uppercase();
+                match (upper.next(), upper.next()) {
+                    (Some(upper), None) => upper.to_string(),
+                    _ => ch.to_string(),
+                }
+            }
+            (Key::Char(ch), None) if style == Style::Vim => match ch {
+                '<' => "lt".to_string(),
+                '|' => "Bar".to_string(),
+                '\\' => "Bslash".to_string(),
+                ch => ch.to_string(),
+            },
+            (key, None) => Node::from(key.clone()).to_string(),
+        };
+
+        let mut prefix = String::new();
+        for (modifier, name) in style.modifiers() {
+            if modifiers & modifier as u8 != 0 {
+                prefix.push_str(name);
+            }
+        }
+
+        let state = match node.state {
+            Some(state) => format!(" ({})", style.state_name(state)),
+            None => String::new(),
+        };
+
+        // Vim wraps anything but a plain character in <...>, including the
+        // state since sequences have no separator
+        let plain = matches!(node.key, Key::Char(ch) if !matches!(ch, '<' | '|' | '\\'));
+        if style == Style::Vim && !(prefix.is_empty() && plain && state.is_empty()) {
+            write!(f, "<{prefix}{key}{state}>")
+        } else {
+            write!(f, "{prefix}{key}{state}")
+        }
+    }
+}
+
+impl Display for Styled<'_, [Node]> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (i, node) in self.value.iter().enumerate() {
+            if i > 0 {
+                f.write_str(self.style.separator())?;
+            }
+            write!(f, "{}", node.styled(self.style))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_seq;
+
+    #[test]
+    fn test_styles() {
+        let styles = [
+            Style::Plain,
+            Style::Mac,
+            Style::Windows,
+            Style::Linux,
+            Style::Emacs,
+            Style::Vim,
+            Style::Symbol,
+        ];
+
+        [
+            (
+                "ctrl-shift-f",
+                [
+                    "ctrl-shift-f",
+                    "⌃⇧F",
+                    "Ctrl+Shift+F",
+                    "Ctrl+Shift+F",
+                    "C-S-f",
+                    "<C-S-f>",
+                    "^⇧f",
+                ],
+            ),
+            (
+                "ctrl-x ctrl-s",
+                [
+                    "ctrl-x ctrl-s",
+                    "⌃X ⌃S",
+                    "Ctrl+X Ctrl+S",
+                    "Ctrl+X Ctrl+S",
+                    "C-x C-s",
+                    "<C-x><C-s>",
+                    "^x ^s",
+                ],
+            ),
+            ("G", ["G", "⇧G", "Shift+G", "Shift+G", "G", "G", "G"]),
+            (
+                "ctrl-kp5 kpenter",
+                [
+                    "ctrl-kp5 kpenter",
+                    "⌃Num 5 Num Enter",
+                    "Ctrl+Num 5 Num Enter",
+                    "Ctrl+Num 5 Num Enter",
+                    "C-<kp-5> <kp-enter>",
+                    "<C-k5><kEnter>",
+                    "^Num 5 Num Enter",
+                ],
+            ),
+            (
+                "capslock shift-volumeup",
+                [
+                    "capslock shift-volumeup",
+                    "⇪ ⇧🔊",
+                    "Caps Lock Shift+Volume Up",
+                    "Caps Lock Shift+Volume Up",
+                    "<Caps_Lock> S-<XF86AudioRaiseVolume>",
+                    "<capslock><S-volumeup>",
+                    "⇪ ⇧🔊",
+                ],
+            ),
+            (
+                "cmd-enter",
+                [
+                    "cmd-enter",
+                    "⌘↩",
+                    "Win+Enter",
+                    "Super+Enter",
+                    "s-RET",
+                    "<D-CR>",
+                    "⌘↩",
+                ],
+            ),
+            (
+                "space f5 <",
+                [
+                    "space f5 <",
+                    "Space F5 <",
+                    "Space F5 <",
+                    "Space F5 <",
+                    "SPC <f5> <",
+                    "<Space><F5><lt>",
+                    "␣ F5 <",
+                ],
+            ),
+            (
+                "release-space ctrl-x:down",
+                [
+                    "release-space press-ctrl-x",
+                    "Space (Release) ⌃X (Press)",
+                    "Space (Release) Ctrl+X (Press)",
+                    "Space (Release) Ctrl+X (Press)",
+                    "SPC (release) C-x (press)",
+                    "<Space (release)><C-x (press)>",
+                    "␣ (release) ^x (press)",
+                ],
+            ),
+            (
+                "g release-g",
+                [
+                    "g release-g",
+                    "G G (Release)",
+                    "G G (Release)",
+                    "G G (Release)",
+                    "g g (release)",
+                    "g<g (release)>",
+                    "g g (release)",
+                ],
+            ),
+            (
+                "playpause numlock",
+                [
+                    "playpause numlock",
+                    "⏯ Num Lock",
+                    "Play/Pause Num Lock",
+                    "Play/Pause Num Lock",
+                    "<media-play-pause> <num_lock>",
+                    "<playpause><numlock>",
+                    "⏯ Num Lock",
+                ],
+            ),
+            (
+                "alt-@digit",
+                [
+                    "alt-@digit",
+                    "⌥@digit",
+                    "Alt+@digit",
+                    "Alt+@digit",
+                    "M-@digit",
+                    "<M-@digit>",
+                    "⌥@digit",
+                ],
+            ),
+        ]
+        .into_iter()
+        .for_each(|(keys, expected)| {
+            let nodes = parse_seq(keys).unwrap();
+            for (style, expected) in styles.iter().zip(expected) {
+                assert_eq!(
+                    nodes.styled(*style).to_string(),
+                    expected,
+                    "{keys} {style:?}"
+                );
+            }
+        });
+    }
+
+    #[test]
+    fn test_unique_names() {
+        use std::collections::HashMap;
+
+        use strum::IntoEnumIterator;
+
+        use crate::parser::MAX_FN_KEY;
+
+        let keys = Key::iter()
+            .filter(|key| {
+                !matches!(
+                    key,
+                    Key::F(_) | Key::Keypad(_) | Key::Char(_) | Key::Group(_)
+                )
+            })
+            .chain((1..=MAX_FN_KEY).map(Key::F))
+            .chain((0..=9).map(Key::Keypad))
+            .collect::<Vec<_>>();
+
+        for style in [
+            Style::Plain,
+            Style::Mac,
+            Style::Windows,
+            Style::Linux,
+            Style::Emacs,
+            Style::Vim,
+            Style::Symbol,
+        ] {
+            let mut names = HashMap::new();
+            for key in &keys {
+                let name = Node::from(key.clone()).styled(style).to_string();
+                if let Some(other) = names.insert(name.clone(), key) {
+                    panic!("{other:?} and {key:?} are both shown as {name} in {style:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_from_os() {
+        assert_eq!(Style::from_os("macos"), Style::Mac);
+        assert_eq!(Style::from_os("windows"), Style::Windows);
+        assert_eq!(Style::from_os("freebsd"), Style::Linux);
+    }
+}
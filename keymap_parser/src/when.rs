@@ -0,0 +1,484 @@
+//! # When Clauses
+//!
+//! This module parses conditions such as `"editorFocus && !readOnly"` into a
+//! small boolean expression ([`Expr`]) over named context keys. A binding with
+//! a condition only applies while the condition holds for the application's
+//! current [`Context`].
+//!
+//! ## Supported Syntax
+//!
+//! ```text
+//! expr    = or
+//! or      = and ("||" and)*
+//! and     = unary ("&&" unary)*
+//! unary   = "!" unary | primary
+//! primary = "(" expr ")" | "true" | "false" | ident (("==" | "!=") value)?
+//! value   = ident | "'" [^']* "'" | '"' [^"]* '"'
+//! ident   = (alnum | "_" | "." | ":" | "-")+
+//! ```
+//!
+//! `!` binds tighter than `&&`, which binds tighter than `||`. A comparison
+//! such as `resourceExtname == .rs` holds if the context key has that value
+//! (see [`Context::equals`]).
+//!
+//! # Examples
+//!
+//! ```
+//! use keymap_parser::when::{parse, Expr};
+//!
+//! let expr = parse("editorFocus && !readOnly").unwrap();
+//!
+//! assert!(expr.eval(&["editorFocus"][..]));
+//! assert!(!expr.eval(&["editorFocus", "readOnly"][..]));
+//! ```
+use std::{
+    borrow::Borrow,
+    collections::{HashMap, HashSet},
+    fmt::{self, Display, Formatter},
+    hash::Hash,
+    str::FromStr,
+};
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::parser::ParseError;
+
+/// A parsed when clause.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Expr {
+    /// A literal `true` or `false`.
+    Bool(bool),
+    /// A named context key, which holds if the context has it set.
+    Key(String),
+    /// A context key compared to a value (`key == value`). `key != value` is
+    /// parsed as its negation.
+    Eq(String, String),
+    /// Negation (`!a`).
+    Not(Box<Expr>),
+    /// Conjunction (`a && b`).
+    And(Box<Expr>, Box<Expr>),
+    /// Disjunction (`a || b`).
+    Or(Box<Expr>, Box<Expr>),
+}
+
+/// The application state a when clause is evaluated against.
+///
+/// Implemented for sets and slices of the keys that are set, maps from keys
+/// to `bool` or to string values, and closures.
+pub trait Context {
+    /// Returns `true` if the context key is set.
+    fn get(&self, key: &str) -> bool;
+
+    /// Returns `true` if the context key has the given value.
+    ///
+    /// By default, only `key == true` and `key == false` can hold, following
+    /// [`Context::get`].
+    fn equals(&self, key: &str, value: &str) -> bool {
+        match value {
+            "true" => self.get(key),
+            "false" => !self.get(key),
+            _ => false,
+        }
+    }
+}
+
+impl<S: Borrow<str> + Hash + Eq> Context for HashSet<S> {
+    fn get(&self, key: &str) -> bool {
+        self.contains(key)
+    }
+}
+
+impl<S: Borrow<str> + Hash + Eq> Context for HashMap<S, bool> {
+    fn get(&self, key: &str) -> bool {
+        self.get(key).copied().unwrap_or(false)
+    }
+}
+
+/// A key is set if its value is not empty.
+impl<S: Borrow<str> + Hash + Eq> Context for HashMap<S, String> {
+    fn get(&self, key: &str) -> bool {
+        self.get(key).is_some_and(|value| !value.is_empty())
+    }
+
+    fn equals(&self, key: &str, value: &str) -> bool {
+        self.get(key).is_some_and(|v| v == value)
+    }
+}
+
+impl<S: AsRef<str>> Context for [S] {
+    fn get(&self, key: &str) -> bool {
+        self.iter().any(|k| k.as_ref() == key)
+    }
+}
+
+impl<S: AsRef<str>> Context for Vec<S> {
+    fn get(&self, key: &str) -> bool {
+        Context::get(self.as_slice(), key)
+    }
+}
+
+impl<F: Fn(&str) -> bool> Context for F {
+    fn get(&self, key: &str) -> bool {
+        self(key)
+    }
+}
+
+impl Expr {
+    /// Evaluates the expression against the given context.
+    pub fn eval<C: Context + ?Sized>(&self, context: &C) -> bool {
+        match self {
+            Expr::Bool(value) => *value,
+            Expr::Key(key) => context.get(key),
+            Expr::Eq(key, value) => context.equals(key, value),
+            Expr::Not(expr) => !expr.eval(context),
+            Expr::And(a, b) => a.eval(context) && b.eval(context),
+            Expr::Or(a, b) => a.eval(context) || b.eval(context),
+        }
+    }
+}
+
+/// Parses a when clause into an [`Expr`].
+///
+/// # Errors
+///
+/// Returns a [`ParseError`] if the input is not a valid expression.
+pub fn parse(input: &str) -> Result<Expr, ParseError> {
+    let mut parser = Parser { input, position: 0 };
+    let expr = parser.parse_or()?;
+
+    parser.skip_whitespace();
+    match parser.peek() {
+        None => Ok(expr),
+        Some(ch) => Err(parser.error(format!("unexpected '{ch}'"))),
+    }
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.input[self.position..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn error(&self, message: String) -> ParseError {
+        ParseError {
+            message,
+            position: self.position,
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    /// Consumes `token` (after any whitespace) if it comes next.
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_whitespace();
+        if self.rest().starts_with(token) {
+            self.position += token.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = self.parse_and()?;
+        while self.eat("||") {
+            expr = Expr::Or(Box::new(expr), Box::new(self.parse_and()?));
+        }
+
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = self.parse_unary()?;
+        while self.eat("&&") {
+            expr = Expr::And(Box::new(expr), Box::new(self.parse_unary()?));
+        }
+
+        Ok(expr)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, ParseError> {
+        if self.eat("!") {
+            return Ok(Expr::Not(Box::new(self.parse_unary()?)));
+        }
+
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, ParseError> {
+        if self.eat("(") {
+            let expr = self.parse_or()?;
+            if !self.eat(")") {
+                return Err(self.error("expected ')'".to_string()));
+            }
+
+            return Ok(expr);
+        }
+
+        let ident = self.parse_ident("a context key")?;
+        let expr = match ident {
+            "true" => return Ok(Expr::Bool(true)),
+            "false" => return Ok(Expr::Bool(false)),
+            _ => Expr::Key(ident.to_string()),
+        };
+
+        let negate = if self.eat("==") {
+            false
+        } else if self.eat("!=") {
+            true
+        } else {
+            return Ok(expr);
+        };
+
+        let value = self.parse_value()?;
+        let expr = Expr::Eq(ident.to_string(), value.to_string());
+        Ok(if negate {
+            Expr::Not(Box::new(expr))
+        } else {
+            expr
+        })
+    }
+
+    /// Parses the value of a comparison, either an identifier or a quoted
+    /// string.
+    fn parse_value(&mut self) -> Result<&'a str, ParseError> {
+        self.skip_whitespace();
+        let Some(quote) = self.peek().filter(|ch| matches!(ch, '\'' | '"')) else {
+            return self.parse_ident("a value");
+        };
+
+        let rest = &self.rest()[1..];
+        match rest.find(quote) {
+            Some(end) => {
+                self.position += end + 2;
+                Ok(&rest[..end])
+            }
+            None => Err(self.error(format!("expected closing {quote}"))),
+        }
+    }
+
+    fn parse_ident(&mut self, expected: &str) -> Result<&'a str, ParseError> {
+        self.skip_whitespace();
+        let ident = self
+            .rest()
+            .split(|ch: char| !is_ident_char(ch))
+            .next()
+            .unwrap_or_default();
+
+        match (ident, self.peek()) {
+            ("", Some(ch)) => Err(self.error(format!("expected {expected}, found '{ch}'"))),
+            ("", None) => Err(self.error(format!("expected {expected}, found end of input"))),
+            (ident, _) => {
+                self.position += ident.len();
+                Ok(ident)
+            }
+        }
+    }
+}
+
+fn is_ident_char(ch: char) -> bool {
+    ch.is_alphanumeric() || matches!(ch, '_' | '.' | ':' | '-')
+}
+
+impl FromStr for Expr {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse(s)
+    }
+}
+
+impl Display for Expr {
+    /// Formats the expression with the fewest parentheses needed to parse it
+    /// back (e.g. `"a && (b || !c)"`).
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Expr::Bool(value) => write!(f, "{value}"),
+            Expr::Key(key) => write!(f, "{key}"),
+            Expr::Eq(key, value) => write!(f, "{key} == {}", Quoted(value)),
+            Expr::Not(expr) => match &**expr {
+                Expr::Eq(key, value) => write!(f, "{key} != {}", Quoted(value)),
+                Expr::And(..) | Expr::Or(..) => write!(f, "!({expr})"),
+                _ => write!(f, "!{expr}"),
+            },
+            Expr::And(a, b) => {
+                let group = |expr: &Expr| matches!(expr, Expr::Or(..));
+                write_operand(f, a, group(a))?;
+                write!(f, " && ")?;
+                write_operand(f, b, group(b) || matches!(**b, Expr::And(..)))
+            }
+            Expr::Or(a, b) => {
+                write!(f, "{a} || ")?;
+                write_operand(f, b, matches!(**b, Expr::Or(..)))
+            }
+        }
+    }
+}
+
+/// A comparison value, quoted unless it reads back as an identifier.
+struct Quoted<'a>(&'a str);
+
+impl Display for Quoted<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let value = self.0;
+        if !value.is_empty() && value.chars().all(is_ident_char) {
+            write!(f, "{value}")
+        } else if value.contains('\'') {
+            write!(f, "\"{value}\"")
+        } else {
+            write!(f, "'{value}'")
+        }
+    }
+}
+
+fn write_operand(f: &mut Formatter<'_>, expr: &Expr, group: bool) -> fmt::Result {
+    if group {
+        write!(f, "({expr})")
+    } else {
+        write!(f, "{expr}")
+    }
+}
+
+/// Custom deserialization for [`Expr`] from a string (e.g. `"a && !b"`).
+impl<'s> Deserialize<'s> for Expr {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'s>,
+    {
+        let expr = String::deserialize(deserializer)?;
+        parse(&expr).map_err(de::Error::custom)
+    }
+}
+
+/// Custom serialization for [`Expr`] into its string form (see [`Display`]).
+impl Serialize for Expr {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(name: &str) -> Box<Expr> {
+        Box::new(Expr::Key(name.to_string()))
+    }
+
+    #[test]
+    fn test_parse() {
+        [
+            ("focus", *key("focus")),
+            ("true", Expr::Bool(true)),
+            ("!readOnly", Expr::Not(key("readOnly"))),
+            ("a && b", Expr::And(key("a"), key("b"))),
+            (
+                "a || b && !c",
+                Expr::Or(
+                    key("a"),
+                    Box::new(Expr::And(key("b"), Box::new(Expr::Not(key("c"))))),
+                ),
+            ),
+            (
+                " ( a || b ) && c ",
+                Expr::And(Box::new(Expr::Or(key("a"), key("b"))), key("c")),
+            ),
+            ("config.editor-mode:vim", *key("config.editor-mode:vim")),
+            (
+                "resourceExtname == .rs",
+                Expr::Eq("resourceExtname".into(), ".rs".into()),
+            ),
+            (
+                "mode != 'insert mode' && a",
+                Expr::And(
+                    Box::new(Expr::Not(Box::new(Expr::Eq(
+                        "mode".into(),
+                        "insert mode".into(),
+                    )))),
+                    key("a"),
+                ),
+            ),
+        ]
+        .into_iter()
+        .for_each(|(input, expected)| assert_eq!(parse(input).unwrap(), expected, "{input}"));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        [
+            ("", 0, "expected a context key, found end of input"),
+            ("a &&", 4, "expected a context key, found end of input"),
+            ("(a || b", 7, "expected ')'"),
+            ("a b", 2, "unexpected 'b'"),
+            ("a & b", 2, "unexpected '&'"),
+            ("!=", 1, "expected a context key, found '='"),
+            ("a ==", 4, "expected a value, found end of input"),
+            ("a == 'b", 5, "expected closing '"),
+        ]
+        .into_iter()
+        .for_each(|(input, position, message)| {
+            let err = parse(input).unwrap_err();
+            assert_eq!(
+                (err.position, err.message.as_str()),
+                (position, message),
+                "{input}"
+            );
+        });
+    }
+
+    #[test]
+    fn test_eval() {
+        let expr = parse("editorFocus && !readOnly || force").unwrap();
+
+        assert!(expr.eval(&["editorFocus"][..]));
+        assert!(!expr.eval(&vec!["editorFocus", "readOnly"]));
+        assert!(expr.eval(&HashSet::from(["readOnly", "force"])));
+        assert!(!expr.eval(&HashMap::from([("editorFocus", false)])));
+        assert!(expr.eval(&|key: &str| key != "readOnly"));
+
+        let expr = parse("lang == rust && !(mode != vim) && debug").unwrap();
+        let context = HashMap::from([
+            ("lang", "rust".to_string()),
+            ("mode", "vim".to_string()),
+            ("debug", "1".to_string()),
+        ]);
+        assert!(expr.eval(&context));
+        assert!(!expr.eval(&HashMap::from([("lang", "rust".to_string())])));
+        assert!(parse("a == true && b == false").unwrap().eval(&["a"][..]));
+    }
+
+    #[test]
+    fn test_format() {
+        [
+            "a && b && c",
+            "a || b || c",
+            "a && (b || c)",
+            "(a || b) && !c",
+            "!(a && b) || false",
+            "a && (b && c)",
+            "!!a",
+            "a == b.c && d != 'e f'",
+            "a == \"it's\"",
+        ]
+        .into_iter()
+        .for_each(|input| {
+            let expr = parse(input).unwrap();
+            assert_eq!(expr.to_string(), input);
+            assert_eq!(parse(&expr.to_string()).unwrap(), expr);
+        });
+    }
+}
@@ -0,0 +1,595 @@
+//! Conflict and shadowing analysis for key bindings.
+//!
+//! [`Config::new`](crate::Config::new) accepts any set of bindings, even when
+//! some of them can never fire or only fire after a timeout. This module
+//! inspects the parsed patterns of every [`Item`] and reports such cases as
+//! structured [`Diagnostic`]s, so applications can warn users before a
+//! configuration is saved.
+//!
+//! | Diagnostic                        | Example                  |
+//! | --------------------------------- | ------------------------ |
+//! | [`Diagnostic::DuplicatePattern`]  | `del` and `delete`       |
+//! | [`Diagnostic::ExactGroupOverlap`] | `a` and `@lower`         |
+//! | [`Diagnostic::GroupOverlap`]      | `@alpha` and `@lower`    |
+//! | [`Diagnostic::StateOverlap`]      | `space` and `space:down` |
+//! | [`Diagnostic::PrefixAmbiguity`]   | `d` and `d d`            |
+//!
+//! Bindings gated by [`Item::when`] clauses are only reported if both clauses
+//! can hold at the same time. Clauses are treated as exclusive when one
+//! requires a context key that the other negates (e.g. `a` and `!a && b`).
+use std::fmt;
+
+use keymap_parser::{
+    node::{CharGroup, Key, State},
+    parse_seq,
+    when::Expr,
+    Node,
+};
+
+use crate::Item;
+
+/// A single key expression bound to a value, as written in [`Item::keys`].
+#[derive(Debug, PartialEq, Eq)]
+pub struct Binding<'a, T> {
+    /// The value (e.g. an action) the key expression is bound to.
+    pub value: &'a T,
+    /// The key expression as written by the user (e.g. `"ctrl-b n"`).
+    pub key: &'a str,
+    /// The [`Item::when`] clause the binding is gated by, if any.
+    pub when: Option<&'a Expr>,
+}
+
+// Manual impls avoid requiring `T: Clone`/`T: Copy`.
+impl<T> Clone for Binding<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Binding<'_, T> {}
+
+impl<T: fmt::Debug> fmt::Display for Binding<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "`{}` ({:?})", self.key, self.value)?;
+        match self.when {
+            Some(when) => write!(f, " when `{when}`"),
+            None => Ok(()),
+        }
+    }
+}
+
+/// A problem found between two bindings of a [`Config`](crate::Config).
+///
+/// Bindings are reported in the order they appear in the configuration, so
+/// `first` always comes before `second`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Diagnostic<'a, T> {
+    /// Both bindings have the same pattern. The later one overwrites the
+    /// earlier one, which can never fire.
+    DuplicatePattern {
+        first: Binding<'a, T>,
+        second: Binding<'a, T>,
+    },
+    /// An exact key overlaps a key group (e.g. `a` and `@lower`). The exact
+    /// binding always takes precedence for the keys it matches.
+    ExactGroupOverlap {
+        exact: Binding<'a, T>,
+        group: Binding<'a, T>,
+    },
+    /// Two key groups match some of the same keys (e.g. `@alpha` and `@lower`).
+    /// Only one of them fires for the shared keys.
+    GroupOverlap {
+        first: Binding<'a, T>,
+        second: Binding<'a, T>,
+    },
+    /// Two bindings of the same keys match some of the same key states (e.g.
+    /// `space` and `space:down`). The binding in the exact state of the key
+    /// event takes precedence.
+    StateOverlap {
+        first: Binding<'a, T>,
+        second: Binding<'a, T>,
+    },
+    /// A complete binding is also the start of a longer one (e.g. `d` and
+    /// `d d`). The shorter binding only fires once the next key breaks the
+    /// sequence or a timeout expires.
+    PrefixAmbiguity {
+        prefix: Binding<'a, T>,
+        longer: Binding<'a, T>,
+    },
+}
+
+impl<'a, T> Diagnostic<'a, T> {
+    /// Returns the two bindings involved, in the order of the variant fields.
+    pub fn bindings(&self) -> (Binding<'a, T>, Binding<'a, T>) {
+        match *self {
+            Diagnostic::DuplicatePattern { first, second }
+            | Diagnostic::GroupOverlap { first, second }
+            | Diagnostic::StateOverlap { first, second } => (first, second),
+            Diagnostic::ExactGroupOverlap { exact, group } => (exact, group),
+            Diagnostic::PrefixAmbiguity { prefix, longer } => (prefix, longer),
+        }
+    }
+
+    /// Returns the condition under which the bindings conflict, i.e. the
+    /// conjunction of their `when` clauses (e.g. `a && b`), or `None` if
+    /// neither is gated.
+    pub fn when(&self) -> Option<Expr> {
+        match self.bindings() {
+            (Binding { when: Some(a), .. }, Binding { when: Some(b), .. }) if a == b => {
+                Some(a.clone())
+            }
+            (Binding { when: Some(a), .. }, Binding { when: Some(b), .. }) => {
+                Some(Expr::And(Box::new(a.clone()), Box::new(b.clone())))
+            }
+            (Binding { when, .. }, Binding { when: None, .. })
+            | (Binding { when: None, .. }, Binding { when, .. }) => when.cloned(),
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Display for Diagnostic<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Diagnostic::DuplicatePattern { first, second } => {
+                write!(f, "{second} overrides the same keys as {first}")
+            }
+            Diagnostic::ExactGroupOverlap { exact, group } => {
+                write!(f, "{exact} shadows part of the key group {group}")
+            }
+            Diagnostic::GroupOverlap { first, second } => {
+                write!(f, "{first} and {second} match some of the same keys")
+            }
+            Diagnostic::StateOverlap { first, second } => {
+                write!(f, "{first} and {second} match some of the same key events")
+            }
+            Diagnostic::PrefixAmbiguity { prefix, longer } => {
+                write!(
+                    f,
+                    "{prefix} is a prefix of {longer} and waits for the next key"
+                )
+            }
+        }
+    }
+}
+
+/// Analyzes the bindings of the given items. See [`Diagnostic`].
+///
+/// Key expressions that fail to parse are skipped.
+pub(crate) fn analyze<T>(items: &[(T, Item)]) -> Vec<Diagnostic<'_, T>> {
+    let patterns = items
+        .iter()
+        .enumerate()
+        .flat_map(|(index, (value, item))| {
+            item.keys.iter().filter_map(move |key| {
+                let nodes = parse_seq(key).ok()?;
+                let when = item.when.as_ref();
+                Some((index, Binding { value, key, when }, nodes))
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let mut diagnostics = Vec::new();
+
+    for (i, (a_index, a, a_nodes)) in patterns.iter().enumerate() {
+        for (b_index, b, b_nodes) in &patterns[i + 1..] {
+            let (first, second) = (*a, *b);
+            let same_item = a_index == b_index;
+
+            // Bindings whose `when` clauses never hold together never compete
+            if exclusive(a.when, b.when) {
+                continue;
+            }
+
+            if a_nodes.len() != b_nodes.len() {
+                let (prefix, longer, short, long) = if a_nodes.len() < b_nodes.len() {
+                    (first, second, a_nodes, b_nodes)
+                } else {
+                    (second, first, b_nodes, a_nodes)
+                };
+
+                if short.iter().zip(long.iter()).all(|(x, y)| overlaps(x, y)) {
+                    diagnostics.push(Diagnostic::PrefixAmbiguity { prefix, longer });
+                }
+                continue;
+            }
+
+            // Overlapping bindings of the same item resolve to the same value
+            if same_item
+                || !a_nodes
+                    .iter()
+                    .zip(b_nodes.iter())
+                    .all(|(x, y)| overlaps(x, y))
+            {
+                continue;
+            }
+
+            let diagnostic = match a_nodes
+                .iter()
+                .zip(b_nodes.iter())
+                .find(|(x, y)| x.key != y.key || x.modifiers != y.modifiers)
+            {
+                None if a_nodes == b_nodes => Diagnostic::DuplicatePattern { first, second },
+                None => Diagnostic::StateOverlap { first, second },
+                Some((x, y)) => match (is_group(x), is_group(y)) {
+                    (false, true) => Diagnostic::ExactGroupOverlap {
+                        exact: first,
+                        group: second,
+                    },
+                    (true, false) => Diagnostic::ExactGroupOverlap {
+                        exact: second,
+                        group: first,
+                    },
+                    _ => Diagnostic::GroupOverlap { first, second },
+                },
+            };
+
+            diagnostics.push(diagnostic);
+        }
+    }
+
+    diagnostics
+}
+
+/// Returns `true` if two `when` clauses can never hold at the same time.
+///
+/// This is a conservative check over the conjuncts of both clauses: they are
+/// exclusive if one of them is `false`, if one is the negation of another
+/// (e.g. `a` and `!a`), or if they compare a key to different values (e.g.
+/// `lang == rust` and `lang == go`). Anything else is assumed to possibly
+/// overlap.
+fn exclusive(a: Option<&Expr>, b: Option<&Expr>) -> bool {
+    fn conjuncts<'e>(expr: &'e Expr, out: &mut Vec<&'e Expr>) {
+        match expr {
+            Expr::And(a, b) => {
+                conjuncts(a, out);
+                conjuncts(b, out);
+            }
+            expr => out.push(expr),
+        }
+    }
+
+    let mut terms = Vec::new();
+    a.into_iter()
+        .chain(b)
+        .for_each(|expr| conjuncts(expr, &mut terms));
+
+    terms.iter().any(|term| match term {
+        Expr::Bool(false) => true,
+        Expr::Not(negated) => terms.contains(&&**negated),
+        Expr::Eq(key, value) => terms
+            .iter()
+            .any(|other| matches!(other, Expr::Eq(k, v) if k == key && v != value)),
+        _ => false,
+    })
+}
+
+fn is_group(node: &Node) -> bool {
+    matches!(node.key, Key::Group(_))
+}
+
+/// Returns `true` if some input key can match both pattern nodes, following
+/// the same rules as the matcher (`@any` ignores modifiers, and a pattern
+/// without a state matches any key event but a release).
+fn overlaps(a: &Node, b: &Node) -> bool {
+    let states = [
+        None,
+        Some(State::Pressed),
+        Some(State::Released),
+        Some(State::Held),
+        Some(State::Repeated),
+    ];
+    if !states
+        .into_iter()
+        .any(|state| State::accepts(a.state, state) && State::accepts(b.state, state))
+    {
+        return false;
+    }
+    if a.key == b.key && a.modifiers == b.modifiers {
+        return true;
+    }
+
+    match (&a.key, &b.key) {
+        (Key::Group(CharGroup::Any), _) | (_, Key::Group(CharGroup::Any)) => true,
+        (Key::Group(group), Key::Char(ch)) | (Key::Char(ch), Key::Group(group)) => {
+            a.modifiers == b.modifiers && group.matches(*ch)
+        }
+        (Key::Group(g1), Key::Group(g2)) => a.modifiers == b.modifiers && groups_overlap(*g1, *g2),
+        _ => false,
+    }
+}
+
+/// Returns `true` if two key groups match some of the same chars.
+///
+/// Only the pairs below are disjoint: digits are not letters, ASCII letters
+/// are not numeric, and no char is both lowercase and uppercase.
+fn groups_overlap(g1: CharGroup, g2: CharGroup) -> bool {
+    use CharGroup::*;
+
+    let disjoint = |a, b| {
+        matches!(
+            (a, b),
+            (
+                Digit,
+                Lower | Upper | Alpha | Lowercase | Uppercase | Alphabetic
+            ) | (Lower, Upper | Uppercase | Numeric)
+                | (Upper, Lowercase | Numeric)
+                | (Alpha, Numeric)
+                | (Lowercase, Uppercase)
+        )
+    };
+
+    !disjoint(g1, g2) && !disjoint(g2, g1)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Config, Item};
+
+    use super::*;
+
+    fn config(items: &[(&'static str, &[&str])]) -> Config<&'static str> {
+        Config::new(
+            items
+                .iter()
+                .map(|(value, keys)| {
+                    let keys = keys.iter().map(|k| k.to_string()).collect();
+                    (*value, Item::new(keys, String::new()))
+                })
+                .collect(),
+        )
+    }
+
+    fn binding<'a>(value: &'a &'static str, key: &'a str) -> Binding<'a, &'static str> {
+        Binding {
+            value,
+            key,
+            when: None,
+        }
+    }
+
+    fn gated<'a>(
+        value: &'a &'static str,
+        key: &'a str,
+        when: &'a Expr,
+    ) -> Binding<'a, &'static str> {
+        Binding {
+            value,
+            key,
+            when: Some(when),
+        }
+    }
+
+    #[test]
+    fn test_duplicate_pattern() {
+        let config = config(&[("Delete", &["del"]), ("Remove", &["delete"])]);
+
+        assert_eq!(
+            config.analyze(),
+            vec![Diagnostic::DuplicatePattern {
+                first: binding(&"Delete", "del"),
+                second: binding(&"Remove", "delete"),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_group_overlaps() {
+        let config = config(&[
+            ("Lower", &["@lower"]),
+            ("A", &["a"]),
+            ("Alpha", &["@alpha"]),
+            ("Digit", &["@digit", "ctrl-@upper"]),
+        ]);
+
+        assert_eq!(
+            config.analyze(),
+            vec![
+                Diagnostic::ExactGroupOverlap {
+                    exact: binding(&"A", "a"),
+                    group: binding(&"Lower", "@lower"),
+                },
+                Diagnostic::GroupOverlap {
+                    first: binding(&"Lower", "@lower"),
+                    second: binding(&"Alpha", "@alpha"),
+                },
+                Diagnostic::ExactGroupOverlap {
+                    exact: binding(&"A", "a"),
+                    group: binding(&"Alpha", "@alpha"),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unicode_group_overlaps() {
+        let config = config(&[
+            ("Letter", &["@alphabetic"]),
+            ("Cyrillic", &["ж"]),
+            ("Upper", &["@uppercase"]),
+            ("Lower", &["@lowercase"]),
+        ]);
+
+        assert_eq!(
+            config.analyze(),
+            vec![
+                Diagnostic::ExactGroupOverlap {
+                    exact: binding(&"Cyrillic", "ж"),
+                    group: binding(&"Letter", "@alphabetic"),
+                },
+                Diagnostic::GroupOverlap {
+                    first: binding(&"Letter", "@alphabetic"),
+                    second: binding(&"Upper", "@uppercase"),
+                },
+                Diagnostic::GroupOverlap {
+                    first: binding(&"Letter", "@alphabetic"),
+                    second: binding(&"Lower", "@lowercase"),
+                },
+                Diagnostic::ExactGroupOverlap {
+                    exact: binding(&"Cyrillic", "ж"),
+                    group: binding(&"Lower", "@lowercase"),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_groups_overlap() {
+        use CharGroup::*;
+
+        let groups = [
+            Digit,
+            Lower,
+            Upper,
+            Alpha,
+            Alnum,
+            Numeric,
+            Lowercase,
+            Uppercase,
+            Alphabetic,
+            Alphanumeric,
+            Any,
+        ];
+
+        // The distinct sets of groups that match a char, as bitmasks.
+        let masks = ('\0'..=char::MAX)
+            .map(|ch| {
+                groups
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, group)| group.matches(ch))
+                    .fold(0u16, |mask, (i, _)| mask | 1 << i)
+            })
+            .collect::<std::collections::BTreeSet<_>>();
+        let shared =
+            |i: usize, j: usize| masks.iter().any(|m| m & (1 << i) != 0 && m & (1 << j) != 0);
+
+        for (i, g1) in groups.iter().enumerate() {
+            for (j, g2) in groups.iter().enumerate() {
+                assert_eq!(groups_overlap(*g1, *g2), shared(i, j), "{g1} {g2}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_state_overlaps() {
+        let config = config(&[
+            ("Jump", &["space"]),
+            ("Land", &["release-space"]),
+            ("Charge", &["space:down"]),
+        ]);
+
+        assert_eq!(
+            config.analyze(),
+            vec![Diagnostic::StateOverlap {
+                first: binding(&"Jump", "space"),
+                second: binding(&"Charge", "space:down"),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_prefix_ambiguity() {
+        let config = config(&[
+            ("Delete", &["d", "d e"]),
+            ("Window", &["ctrl-b n", "ctrl-b c"]),
+            ("Prefix", &["ctrl-b"]),
+        ]);
+
+        assert_eq!(
+            config.analyze(),
+            vec![
+                Diagnostic::PrefixAmbiguity {
+                    prefix: binding(&"Delete", "d"),
+                    longer: binding(&"Delete", "d e"),
+                },
+                Diagnostic::PrefixAmbiguity {
+                    prefix: binding(&"Prefix", "ctrl-b"),
+                    longer: binding(&"Window", "ctrl-b n"),
+                },
+                Diagnostic::PrefixAmbiguity {
+                    prefix: binding(&"Prefix", "ctrl-b"),
+                    longer: binding(&"Window", "ctrl-b c"),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_when() {
+        let (a, not_a, b, not_a_and_b) = (
+            "a".parse().unwrap(),
+            "!a".parse().unwrap(),
+            "b".parse().unwrap(),
+            "b && !a".parse().unwrap(),
+        );
+        let item = |key: &str, when: &Expr| {
+            Item::new(vec![key.into()], String::new()).with_when(Some(when.clone()))
+        };
+        let config = Config::new(vec![
+            ("Newline", Item::new(vec!["enter".into()], String::new())),
+            ("Submit", item("enter", &a)),
+            ("Cancel", item("enter", &not_a)),
+            ("Delete", item("d", &a)),
+            ("DeleteLine", item("d d", &not_a_and_b)),
+            ("Down", item("d j", &b)),
+        ]);
+
+        // Only bindings whose clauses are provably exclusive are skipped
+        let diagnostics = config.analyze();
+        assert_eq!(
+            diagnostics,
+            vec![
+                Diagnostic::DuplicatePattern {
+                    first: binding(&"Newline", "enter"),
+                    second: gated(&"Submit", "enter", &a),
+                },
+                Diagnostic::DuplicatePattern {
+                    first: binding(&"Newline", "enter"),
+                    second: gated(&"Cancel", "enter", &not_a),
+                },
+                Diagnostic::PrefixAmbiguity {
+                    prefix: gated(&"Delete", "d", &a),
+                    longer: gated(&"Down", "d j", &b),
+                },
+            ]
+        );
+
+        assert_eq!(diagnostics[0].when(), Some(a.clone()));
+        assert_eq!(diagnostics[2].when(), Some("a && b".parse().unwrap()));
+        assert_eq!(
+            diagnostics[2].to_string(),
+            r#"`d` ("Delete") when `a` is a prefix of `d j` ("Down") when `b` and waits for the next key"#
+        );
+    }
+
+    #[test]
+    fn test_exclusive() {
+        let exclusive =
+            |a: &str, b: &str| exclusive(Some(&a.parse().unwrap()), Some(&b.parse().unwrap()));
+
+        assert!(exclusive("a", "!a"));
+        assert!(exclusive("!a && b", "c && a"));
+        assert!(exclusive("a && !a", "b"));
+        assert!(exclusive("false", "b"));
+        assert!(!exclusive("a", "b"));
+        assert!(!exclusive("a || b", "!a"));
+        assert!(!exclusive("!a", "!b"));
+        assert!(!exclusive("a", "a"));
+        assert!(exclusive("lang == rust", "b && lang == go"));
+        assert!(exclusive("lang == rust", "lang != rust"));
+        assert!(!exclusive("lang == rust", "lang != go"));
+    }
+
+    #[test]
+    fn test_display() {
+        let diagnostic = Diagnostic::PrefixAmbiguity {
+            prefix: binding(&"Delete", "d"),
+            longer: binding(&"DeleteLine", "d d"),
+        };
+
+        assert_eq!(
+            diagnostic.to_string(),
+            r#"`d` ("Delete") is a prefix of `d d` ("DeleteLine") and waits for the next key"#
+        );
+    }
+}
@@ -10,6 +10,15 @@
 //! - Implements `IntoKeyMap`, `ToKeyMap`, and `FromKeyMap` for `KeyEvent`.
 //! - Converts between `KeyEvent` (from crossterm) and internal `KeyMap` format.
 //!
+//! # Key Event Kinds
+//! The `KeyEventKind` of an event maps to the key state of the `KeyMap`:
+//! `Repeat` and `Release` become `repeat` and `release`, while `Press` has no
+//! state, so presses compare equal to keys parsed without one.
+//! crossterm only reports repeats and releases once
+//! `KeyboardEnhancementFlags::REPORT_EVENT_TYPES` is pushed. Patterns without
+//! a state (e.g. `a`) ignore releases, so each key fires once; bind
+//! `release-a` or `a:up` to react to releases.
+//!
 //! # Limitations
 //! - Some `KeyCode` variants are not supported and will return an error.
 //! - Key groups (e.g., `@any`) are not reversible to `KeyEvent` due to the loss of specificity.
@@ -24,8 +33,10 @@
 //! let key = parse("ctrl-a").unwrap();
 //! assert_eq!(key, KeyEvent::new(KeyCode::Char('a'), KeyModifiers::CONTROL));
 //! ```
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
-use keymap_parser::{self as parser, Key, Modifier, Node};
+use crossterm::event::{
+    KeyCode, KeyEvent, KeyEventKind, KeyEventState, KeyModifiers, MediaKeyCode,
+};
+use keymap_parser::{self as parser, node::State, Key, Modifier, Node};
 
 use crate::{
     keymap::{FromKeyMap, IntoKeyMap, KeyMap, ToKeyMap},
@@ -62,9 +73,26 @@ impl ToKeyMap for KeyEvent {
     /// - Returns `Error::UnsupportedKey` if the `KeyEvent` variant is not supported for conversion.
     fn to_keymap(&self) -> Result<KeyMap, Error> {
         let KeyEvent {
-            code, modifiers, ..
+            code,
+            modifiers,
+            kind,
+            state,
         } = self;
+        let keypad = state.contains(KeyEventState::KEYPAD);
         let key = match code {
+            // Keypad keys are reported as regular keys with the keypad state
+            KeyCode::Enter if keypad => Key::KeypadEnter,
+            KeyCode::Char(c) if keypad => match c {
+                '+' => Key::KeypadPlus,
+                '-' => Key::KeypadMinus,
+                '*' => Key::KeypadMultiply,
+                '/' => Key::KeypadDivide,
+                '.' | ',' => Key::KeypadDecimal,
+                c => match c.to_digit(10) {
+                    Some(n) => Key::Keypad(n as u8),
+                    None => Key::Char(*c),
+                },
+            },
             KeyCode::BackTab => Key::BackTab,
             KeyCode::Backspace => Key::Backspace,
             KeyCode::Char(' ') => Key::Space,
@@ -74,7 +102,7 @@ impl ToKeyMap for KeyEvent {
             KeyCode::End => Key::End,
             KeyCode::Enter => Key::Enter,
             KeyCode::Esc => Key::Esc,
-            KeyCode::F(n) => Key::F(*n),
+            KeyCode::F(n) if *n <= parser::MAX_FN_KEY => Key::F(*n),
             KeyCode::Home => Key::Home,
             KeyCode::Insert => Key::Insert,
             KeyCode::Left => Key::Left,
@@ -83,6 +111,24 @@ impl ToKeyMap for KeyEvent {
             KeyCode::Right => Key::Right,
             KeyCode::Tab => Key::Tab,
             KeyCode::Up => Key::Up,
+            KeyCode::CapsLock => Key::CapsLock,
+            KeyCode::ScrollLock => Key::ScrollLock,
+            KeyCode::NumLock => Key::NumLock,
+            KeyCode::PrintScreen => Key::PrintScreen,
+            KeyCode::Pause => Key::Pause,
+            KeyCode::Menu => Key::Menu,
+            KeyCode::Media(MediaKeyCode::Play) => Key::Play,
+            KeyCode::Media(MediaKeyCode::Pause) => Key::MediaPause,
+            KeyCode::Media(MediaKeyCode::PlayPause) => Key::PlayPause,
+            KeyCode::Media(MediaKeyCode::Stop) => Key::Stop,
+            KeyCode::Media(MediaKeyCode::FastForward) => Key::FastForward,
+            KeyCode::Media(MediaKeyCode::Rewind) => Key::Rewind,
+            KeyCode::Media(MediaKeyCode::TrackNext) => Key::NextTrack,
+            KeyCode::Media(MediaKeyCode::TrackPrevious) => Key::PrevTrack,
+            KeyCode::Media(MediaKeyCode::Record) => Key::Record,
+            KeyCode::Media(MediaKeyCode::RaiseVolume) => Key::VolumeUp,
+            KeyCode::Media(MediaKeyCode::LowerVolume) => Key::VolumeDown,
+            KeyCode::Media(MediaKeyCode::MuteVolume) => Key::Mute,
             code => {
                 return Err(Error::UnsupportedKey(format!(
                     "Unsupported KeyEvent {code:?}"
@@ -90,7 +136,7 @@ impl ToKeyMap for KeyEvent {
             }
         };
 
-        Ok(Node::new(modifiers_from_backend(modifiers), key))
+        Ok(Node::new(modifiers_from_backend(modifiers), key).with_state(state_from_backend(kind)))
     }
 }
 
@@ -120,6 +166,38 @@ impl FromKeyMap for KeyEvent {
             Key::Tab => KeyCode::Tab,
             Key::Space => KeyCode::Char(' '),
             Key::Up => KeyCode::Up,
+            Key::CapsLock => KeyCode::CapsLock,
+            Key::ScrollLock => KeyCode::ScrollLock,
+            Key::NumLock => KeyCode::NumLock,
+            Key::PrintScreen => KeyCode::PrintScreen,
+            Key::Pause => KeyCode::Pause,
+            Key::Menu => KeyCode::Menu,
+            Key::Keypad(n) => match char::from_digit(n.into(), 10) {
+                Some(c) => KeyCode::Char(c),
+                None => {
+                    return Err(Error::UnsupportedKey(format!(
+                        "Keypad key {n} not supported"
+                    )))
+                }
+            },
+            Key::KeypadEnter => KeyCode::Enter,
+            Key::KeypadPlus => KeyCode::Char('+'),
+            Key::KeypadMinus => KeyCode::Char('-'),
+            Key::KeypadMultiply => KeyCode::Char('*'),
+            Key::KeypadDivide => KeyCode::Char('/'),
+            Key::KeypadDecimal => KeyCode::Char('.'),
+            Key::Play => KeyCode::Media(MediaKeyCode::Play),
+            Key::MediaPause => KeyCode::Media(MediaKeyCode::Pause),
+            Key::PlayPause => KeyCode::Media(MediaKeyCode::PlayPause),
+            Key::Stop => KeyCode::Media(MediaKeyCode::Stop),
+            Key::FastForward => KeyCode::Media(MediaKeyCode::FastForward),
+            Key::Rewind => KeyCode::Media(MediaKeyCode::Rewind),
+            Key::NextTrack => KeyCode::Media(MediaKeyCode::TrackNext),
+            Key::PrevTrack => KeyCode::Media(MediaKeyCode::TrackPrevious),
+            Key::Record => KeyCode::Media(MediaKeyCode::Record),
+            Key::VolumeUp => KeyCode::Media(MediaKeyCode::RaiseVolume),
+            Key::VolumeDown => KeyCode::Media(MediaKeyCode::LowerVolume),
+            Key::Mute => KeyCode::Media(MediaKeyCode::MuteVolume),
             Key::Group(group) => {
                 return Err(Error::UnsupportedKey(format!(
                 "Group {group:?} not supported. There's no way to map char group back to KeyEvent"
@@ -127,7 +205,39 @@ impl FromKeyMap for KeyEvent {
             }
         };
 
-        Ok(KeyEvent::new(key, modifiers_from_node(keymap.modifiers)))
+        let state = if keymap.key.is_keypad() {
+            KeyEventState::KEYPAD
+        } else {
+            KeyEventState::NONE
+        };
+
+        Ok(KeyEvent::new_with_kind_and_state(
+            key,
+            modifiers_from_node(keymap.modifiers),
+            kind_from_node(keymap.state),
+            state,
+        ))
+    }
+}
+
+/// Converts a `KeyEventKind` into the key state of a `Node`. Presses have no
+/// state.
+fn state_from_backend(value: &KeyEventKind) -> Option<State> {
+    match value {
+        KeyEventKind::Press => None,
+        KeyEventKind::Repeat => Some(State::Repeated),
+        KeyEventKind::Release => Some(State::Released),
+    }
+}
+
+/// Converts the key state of a `Node` into a `KeyEventKind`.
+///
+/// crossterm has no kind for held keys, so they are reported as presses.
+fn kind_from_node(value: Option<State>) -> KeyEventKind {
+    match value {
+        Some(State::Repeated) => KeyEventKind::Repeat,
+        Some(State::Released) => KeyEventKind::Release,
+        Some(State::Pressed | State::Held) | None => KeyEventKind::Press,
     }
 }
 
@@ -156,7 +266,7 @@ fn modifiers_from_node(value: parser::Modifiers) -> KeyModifiers {
 
 #[cfg(test)]
 mod tests {
-    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
     use keymap_parser as parser;
     use serde::Deserialize;
 
@@ -169,6 +279,15 @@ mod tests {
         )
     }
 
+    fn keypad(code: KeyCode) -> KeyEvent {
+        KeyEvent::new_with_kind_and_state(
+            code,
+            KeyModifiers::NONE,
+            KeyEventKind::Press,
+            KeyEventState::KEYPAD,
+        )
+    }
+
     #[test]
     fn test_parse() {
         let alt_node = alt_node();
@@ -195,12 +314,22 @@ mod tests {
         [
             (KeyEvent::from(KeyCode::Char('[')), "["),
             (KeyEvent::from(KeyCode::Delete), "del"),
+            (KeyEvent::from(KeyCode::F(24)), "f24"),
+            (KeyEvent::from(KeyCode::CapsLock), "capslock"),
+            (
+                KeyEvent::from(KeyCode::Media(MediaKeyCode::PlayPause)),
+                "playpause",
+            ),
+            (keypad(KeyCode::Char('5')), "kp5"),
+            (keypad(KeyCode::Enter), "kpenter"),
             (alt_a, "alt-ctrl-shift-a"),
         ]
         .map(|(key, code)| {
             let node = parser::parse(code).unwrap();
             assert_eq!(key.to_keymap().unwrap(), node);
         });
+
+        assert!(KeyEvent::from(KeyCode::F(36)).to_keymap().is_err());
     }
 
     #[test]
@@ -213,6 +342,12 @@ mod tests {
         [
             (KeyEvent::from(KeyCode::Char('[')), "["),
             (KeyEvent::from(KeyCode::Delete), "del"),
+            (KeyEvent::from(KeyCode::F(35)), "f35"),
+            (
+                KeyEvent::from(KeyCode::Media(MediaKeyCode::RaiseVolume)),
+                "volumeup",
+            ),
+            (keypad(KeyCode::Char('+')), "kpplus"),
             (alt_a, "alt-ctrl-shift-a"),
         ]
         .map(|(key, code)| {
@@ -221,6 +356,46 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_kind() {
+        [
+            (KeyEventKind::Press, "a"),
+            (KeyEventKind::Repeat, "repeat-a"),
+            (KeyEventKind::Release, "a:up"),
+        ]
+        .into_iter()
+        .for_each(|(kind, code)| {
+            let key = KeyEvent::new_with_kind(KeyCode::Char('a'), KeyModifiers::NONE, kind);
+            let node = parser::parse(code).unwrap();
+
+            assert_eq!(key.to_keymap().unwrap(), node);
+            assert_eq!(KeyEvent::from_keymap(node).unwrap(), key);
+        });
+    }
+
+    #[test]
+    fn test_keypad_fallback() {
+        let config: crate::Config<String> = toml::from_str(
+            r#"
+Confirm = { keys = ["enter"], description = "Confirm" }
+Count = { keys = ["@digit"], description = "Count" }
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.get(&keypad(KeyCode::Enter)),
+            Some(&"Confirm".to_string())
+        );
+        assert_eq!(
+            config.get(&keypad(KeyCode::Char('7'))),
+            Some(&"Count".to_string())
+        );
+
+        let node = keypad(KeyCode::Char('7')).to_keymap().unwrap();
+        assert_eq!(<u8 as crate::KeyGroupValue>::from_keymap_node(&node), 7);
+    }
+
     #[test]
     fn test_deserialize() {
         use std::collections::HashMap;
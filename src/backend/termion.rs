@@ -66,7 +66,7 @@ impl ToKeyMap for KeyEvent {
             KeyEvent::Char('\n') => (Key::Enter, 0),
             KeyEvent::Esc => (Key::Esc, 0),
             KeyEvent::Home => (Key::Home, 0),
-            KeyEvent::F(n) => (Key::F(*n), 0),
+            KeyEvent::F(n) if *n <= parser::MAX_FN_KEY => (Key::F(*n), 0),
             KeyEvent::Insert => (Key::Insert, 0),
             KeyEvent::Left => (Key::Left, 0),
             KeyEvent::PageDown => (Key::PageDown, 0),
@@ -119,6 +119,12 @@ impl FromKeyMap for KeyEvent {
                     "Group {group:?} not supported. Cannot map char group back to KeyEvent"
                 )))
             }
+            // termion has no lock, keypad or media keys
+            key => {
+                return Err(Error::UnsupportedKey(format!(
+                    "Key {key:?} not supported by termion"
+                )))
+            }
         };
 
         match key {
@@ -154,6 +160,7 @@ mod tests {
             ("A", KeyEvent::Char('A')),
             ("enter", KeyEvent::Char('\n')),
             ("ctrl-a", KeyEvent::Ctrl('a')),
+            ("f20", KeyEvent::F(20)),
         ]
         .map(|(s, node)| {
             assert_eq!(node, parse(s).unwrap());
@@ -167,11 +174,15 @@ mod tests {
         [
             (KeyEvent::Char('['), "["),
             (KeyEvent::Delete, "del"),
+            (KeyEvent::F(13), "f13"),
             (alt_a, "alt-a"),
         ]
         .map(|(key, code)| {
             let node = parser::parse(code).unwrap();
             assert_eq!(key.to_keymap().unwrap(), node);
         });
+
+        assert!(KeyEvent::F(36).to_keymap().is_err());
+        assert!(KeyEvent::from_keymap(parser::parse("capslock").unwrap()).is_err());
     }
 }
@@ -25,7 +25,7 @@
 //! assert_eq!(event.key(), "b");
 //! assert_eq!(event.alt_key(), true);
 //! ```
-use keymap_parser::{self as parser, Key, Modifier, Node};
+use keymap_parser::{self as parser, node::State, Key, Modifier, Node};
 use web_sys::{KeyboardEvent, KeyboardEventInit};
 
 use crate::{
@@ -69,8 +69,19 @@ impl ToKeyMap for KeyboardEvent {
 impl ToKeyMap for &KeyboardEvent {
     fn to_keymap(&self) -> Result<KeyMap, Error> {
         let modifiers = modifiers_from_backend(self);
+        let keypad = self.location() == KeyboardEvent::DOM_KEY_LOCATION_NUMPAD;
 
         let key = match self.key().as_str() {
+            // Keypad keys are reported as regular keys on the numpad location
+            "Enter" if keypad => Key::KeypadEnter,
+            "+" if keypad => Key::KeypadPlus,
+            "-" if keypad => Key::KeypadMinus,
+            "*" if keypad => Key::KeypadMultiply,
+            "/" if keypad => Key::KeypadDivide,
+            "." | "," if keypad => Key::KeypadDecimal,
+            s if keypad && s.len() == 1 && s.as_bytes()[0].is_ascii_digit() => {
+                Key::Keypad(s.as_bytes()[0] - b'0')
+            }
             // Backtab = Tab + Shift
             "Tab" if modifiers & Modifier::Shift as u8 != 0 => Key::BackTab,
             "Backspace" => Key::Backspace,
@@ -88,20 +99,37 @@ impl ToKeyMap for &KeyboardEvent {
             " " => Key::Space,
             "Tab" => Key::Tab,
             "ArrowUp" => Key::Up,
+            "CapsLock" => Key::CapsLock,
+            "ScrollLock" => Key::ScrollLock,
+            "NumLock" => Key::NumLock,
+            "PrintScreen" => Key::PrintScreen,
+            "Pause" => Key::Pause,
+            "ContextMenu" => Key::Menu,
+            "MediaPlay" => Key::Play,
+            "MediaPause" => Key::MediaPause,
+            "MediaPlayPause" => Key::PlayPause,
+            "MediaStop" => Key::Stop,
+            "MediaFastForward" => Key::FastForward,
+            "MediaRewind" => Key::Rewind,
+            "MediaTrackNext" => Key::NextTrack,
+            "MediaTrackPrevious" => Key::PrevTrack,
+            "MediaRecord" => Key::Record,
+            "AudioVolumeUp" => Key::VolumeUp,
+            "AudioVolumeDown" => Key::VolumeDown,
+            "AudioVolumeMute" => Key::Mute,
             // Fx
-            s if s.starts_with('F') && s.len() > 1 => {
-                if let Ok(n) = s[1..].parse::<u8>() {
-                    Key::F(n)
-                } else {
+            s if s.starts_with('F') && s.len() > 1 => match s[1..].parse::<u8>() {
+                Ok(n) if n <= parser::MAX_FN_KEY => Key::F(n),
+                _ => {
                     return Err(Error::UnsupportedKey(format!(
                         "Unsupported KeyboardEvent key: {s:?}"
-                    )));
+                    )))
                 }
-            }
+            },
             s => Key::Char(s.chars().next().unwrap_or_default()),
         };
 
-        Ok(Node::new(modifiers_from_backend(self), key))
+        Ok(Node::new(modifiers_from_backend(self), key).with_state(state_from_backend(self)))
     }
 }
 
@@ -131,6 +159,31 @@ impl FromKeyMap for KeyboardEvent {
             Key::Tab => "Tab".to_string(),
             Key::Space => " ".to_string(),
             Key::Up => "ArrowUp".to_string(),
+            Key::CapsLock => "CapsLock".to_string(),
+            Key::ScrollLock => "ScrollLock".to_string(),
+            Key::NumLock => "NumLock".to_string(),
+            Key::PrintScreen => "PrintScreen".to_string(),
+            Key::Pause => "Pause".to_string(),
+            Key::Menu => "ContextMenu".to_string(),
+            Key::Keypad(n) => n.to_string(),
+            Key::KeypadEnter => "Enter".to_string(),
+            Key::KeypadPlus => "+".to_string(),
+            Key::KeypadMinus => "-".to_string(),
+            Key::KeypadMultiply => "*".to_string(),
+            Key::KeypadDivide => "/".to_string(),
+            Key::KeypadDecimal => ".".to_string(),
+            Key::Play => "MediaPlay".to_string(),
+            Key::MediaPause => "MediaPause".to_string(),
+            Key::PlayPause => "MediaPlayPause".to_string(),
+            Key::Stop => "MediaStop".to_string(),
+            Key::FastForward => "MediaFastForward".to_string(),
+            Key::Rewind => "MediaRewind".to_string(),
+            Key::NextTrack => "MediaTrackNext".to_string(),
+            Key::PrevTrack => "MediaTrackPrevious".to_string(),
+            Key::Record => "MediaRecord".to_string(),
+            Key::VolumeUp => "AudioVolumeUp".to_string(),
+            Key::VolumeDown => "AudioVolumeDown".to_string(),
+            Key::Mute => "AudioVolumeMute".to_string(),
             Key::Group(group) => {
                 return Err(Error::UnsupportedKey(format!(
                 "Group {group:?} not supported. There's no way to map char group back to KeyboardEvent"
@@ -140,16 +193,38 @@ impl FromKeyMap for KeyboardEvent {
 
         let event_init = KeyboardEventInit::new();
         event_init.set_key(&key_str);
+        if keymap.key.is_keypad() {
+            event_init.set_location(KeyboardEvent::DOM_KEY_LOCATION_NUMPAD);
+        }
         event_init.set_alt_key(keymap.modifiers & Modifier::Alt as u8 != 0);
         event_init.set_ctrl_key(keymap.modifiers & Modifier::Ctrl as u8 != 0);
         event_init.set_meta_key(keymap.modifiers & Modifier::Cmd as u8 != 0);
         event_init.set_shift_key(keymap.modifiers & Modifier::Shift as u8 != 0);
 
-        KeyboardEvent::new_with_keyboard_event_init_dict("keydown", &event_init)
+        if keymap.state == Some(State::Repeated) {
+            event_init.set_repeat(true);
+        }
+
+        let event_type = match keymap.state {
+            Some(State::Released) => "keyup",
+            _ => "keydown",
+        };
+        KeyboardEvent::new_with_keyboard_event_init_dict(event_type, &event_init)
             .map_err(|e| Error::UnsupportedKey(format!("Failed to create KeyboardEvent: {:?}", e)))
     }
 }
 
+/// Derives the key state from the event type (`keyup`) and the `repeat` flag.
+///
+/// Presses have no state, so they compare equal to keys parsed without one.
+fn state_from_backend(value: &KeyboardEvent) -> Option<State> {
+    match value.type_().as_str() {
+        "keyup" => Some(State::Released),
+        "keydown" if value.repeat() => Some(State::Repeated),
+        _ => None,
+    }
+}
+
 /// Converts a `KeyboardEvent` into a `parser::Modifiers` bitfield.
 fn modifiers_from_backend(value: &KeyboardEvent) -> parser::Modifiers {
     let mut modifiers = 0;
@@ -192,6 +267,9 @@ mod tests {
         [
             ("a", true, true, true, false, "alt-ctrl-shift-a"),
             ("F1", false, false, false, false, "f1"),
+            ("F24", false, false, false, false, "f24"),
+            ("AudioVolumeMute", false, false, false, false, "mute"),
+            ("ContextMenu", false, false, false, false, "menu"),
             ("Delete", false, false, false, false, "del"),
         ]
         .into_iter()
@@ -214,4 +292,34 @@ mod tests {
         assert!(event.shift_key());
         assert!(!event.meta_key());
     }
+
+    #[wasm_bindgen_test]
+    fn test_keypad_fallback() {
+        let config: crate::Config<String> = toml::from_str(
+            r#"
+Confirm = { keys = ["enter"], description = "Confirm" }
+Count = { keys = ["@digit"], description = "Count" }
+"#,
+        )
+        .unwrap();
+        let numpad = |key: &str| {
+            let event_init = KeyboardEventInit::new();
+            event_init.set_key(key);
+            event_init.set_location(KeyboardEvent::DOM_KEY_LOCATION_NUMPAD);
+            KeyboardEvent::new_with_keyboard_event_init_dict("keydown", &event_init).unwrap()
+        };
+
+        assert_eq!(config.get(&numpad("Enter")), Some(&"Confirm".to_string()));
+        assert_eq!(config.get(&numpad("7")), Some(&"Count".to_string()));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_state() {
+        ["a", "release-a", "repeat-a"].iter().for_each(|key| {
+            let keymap = parser::parse(key).unwrap();
+            let event = KeyboardEvent::from_keymap(keymap.clone()).unwrap();
+
+            assert_eq!(event.to_keymap().unwrap(), keymap);
+        });
+    }
 }
@@ -0,0 +1,495 @@
+//! Cheat-sheet rendering for key bindings.
+//!
+//! [`Cheatsheet`] turns a [`Config<T>`] into a table of keys and their help
+//! text, ready for a `--help-keys` flag, a help popup or a docs page.
+//!
+//! | Format               | Output                                     |
+//! | -------------------- | ------------------------------------------ |
+//! | [`Format::Text`]     | Aligned plain-text columns                 |
+//! | [`Format::Markdown`] | Markdown tables, one per group             |
+//! | [`Format::Html`]     | HTML tables, one per group                 |
+//! | [`Format::Ansi`]     | Aligned columns with ANSI terminal colours |
+//!
+//! Each row shows the keys of an [`Item`] (with [`Item::symbol`] standing in
+//! for the first key), its [`Item::help`] and its [`Item::description`].
+//! Columns that are empty for every item are omitted. Rows are grouped by
+//! [`Item::group`] and sorted by [`Item::order`], as in
+//! [`Config::grouped_items`].
+use std::fmt::Write;
+
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::{style::Style, Config, Item};
+
+// ANSI colours
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const COLOR_KEY: &str = "\x1b[38;2;148;226;213m";
+const COLOR_HELP: &str = "\x1b[38;2;249;226;175m";
+const COLOR_TEXT: &str = "\x1b[38;2;166;173;200m";
+
+/// The output format of a [`Cheatsheet`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Aligned plain-text columns.
+    Text,
+    /// Markdown tables.
+    Markdown,
+    /// HTML tables.
+    Html,
+    /// Aligned columns with ANSI terminal colours.
+    Ansi,
+}
+
+type GroupFn<'a, T> = Box<dyn Fn(&T, &Item) -> Option<String> + 'a>;
+
+/// A renderer for the bindings of a [`Config<T>`].
+///
+/// # Example
+///
+/// ```
+/// # use keymap::{Config, Format};
+/// let config: Config<String> = toml::from_str(r#"
+///     Jump = { keys = ["space"], symbol = "␣", help = "jump" }
+///     Quit = { keys = ["q", "esc"], description = "Quit the app" }
+/// "#).unwrap();
+///
+/// let text = config
+///     .cheatsheet()
+///     .max_description_width(20)
+///     .render(Format::Text);
+///
+/// assert_eq!(text, "\
+/// ␣       jump
+/// q, esc        Quit the app
+/// ");
+/// ```
+pub struct Cheatsheet<'a, T> {
+    config: &'a Config<T>,
+    key_width: Option<usize>,
+    description_width: Option<usize>,
+    group: Option<GroupFn<'a, T>>,
+    style: Style,
+}
+
+/// A single row of the cheat sheet, before padding.
+struct Row {
+    key: String,
+    help: String,
+    description: String,
+}
+
+/// Rows sharing the same group (`None` for ungrouped items).
+type Section = (Option<String>, Vec<Row>);
+
+impl<T> Config<T> {
+    /// Returns a [`Cheatsheet`] renderer for this config.
+    pub fn cheatsheet(&self) -> Cheatsheet<'_, T> {
+        Cheatsheet::new(self)
+    }
+}
+
+impl<'a, T> Cheatsheet<'a, T> {
+    /// Creates a renderer for the given config.
+    pub fn new(config: &'a Config<T>) -> Self {
+        Self {
+            config,
+            key_width: None,
+            description_width: None,
+            group: None,
+            style: Style::Plain,
+        }
+    }
+
+    /// Truncates the key column to `width` terminal columns.
+    pub fn max_key_width(mut self, width: usize) -> Self {
+        self.key_width = Some(width);
+        self
+    }
+
+    /// Truncates the help and description columns to `width` terminal columns.
+    pub fn max_description_width(mut self, width: usize) -> Self {
+        self.description_width = Some(width);
+        self
+    }
+
+    /// Groups items under the heading returned by `f` instead of
+    /// [`Item::group`]. Items without a group are listed first, and groups
+    /// follow in order of first appearance.
+    pub fn group_by<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&T, &Item) -> Option<String> + 'a,
+    {
+        self.group = Some(Box::new(f));
+        self
+    }
+
+    /// Formats keys and auto-filled symbols in `style`, e.g.
+    /// [`Style::native`] to follow the conventions of the user's platform.
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Renders the cheat sheet in the given format.
+    pub fn render(&self, format: Format) -> String {
+        let sections = self.sections();
+        let rows = || sections.iter().flat_map(|(_, rows)| rows);
+        let widths = [
+            rows().map(|row| width(&row.key)).max().unwrap_or(0),
+            rows().map(|row| width(&row.help)).max().unwrap_or(0),
+            rows().map(|row| width(&row.description)).max().unwrap_or(0),
+        ];
+
+        let mut out = String::new();
+        match format {
+            Format::Text => render_columns(&mut out, &sections, widths, false),
+            Format::Ansi => render_columns(&mut out, &sections, widths, true),
+            Format::Markdown => render_markdown(&mut out, &sections, widths),
+            Format::Html => render_html(&mut out, &sections, widths),
+        }
+
+        out
+    }
+
+    fn sections(&self) -> Vec<Section> {
+        let mut sections: Vec<Section> = vec![(None, Vec::new())];
+
+        for (t, item) in self.config.sorted_items() {
+            // Disabled items have nothing to show
+            if item.keys.is_empty() {
+                continue;
+            }
+
+            let group = match &self.group {
+                Some(f) => f(t, item),
+                None => item.group.clone(),
+            };
+            let row = Row {
+                key: truncate(&keys(item, self.style), self.key_width),
+                help: truncate(
+                    item.help.as_deref().unwrap_or_default(),
+                    self.description_width,
+                ),
+                description: truncate(&item.description, self.description_width),
+            };
+
+            match sections.iter_mut().find(|(name, _)| *name == group) {
+                Some((_, rows)) => rows.push(row),
+                None => sections.push((group, vec![row])),
+            }
+        }
+
+        sections.retain(|(_, rows)| !rows.is_empty());
+        sections
+    }
+}
+
+/// Joins the keys of an item, showing its symbol in place of the first key.
+fn keys(item: &Item, style: Style) -> String {
+    let mut keys = item.styled_keys(style);
+    if let (Some(first), Some(symbol)) = (keys.first_mut(), item.styled_symbol(style)) {
+        *first = symbol;
+    }
+
+    keys.join(", ")
+}
+
+/// Returns the number of terminal columns `s` occupies, counting wide
+/// characters such as CJK and emoji as two.
+fn width(s: &str) -> usize {
+    s.width()
+}
+
+fn truncate(s: &str, max: Option<usize>) -> String {
+    match max {
+        Some(max) if width(s) > max => {
+            let mut budget = max.saturating_sub(1);
+            let mut s = s
+                .chars()
+                .take_while(|c| {
+                    let w = c.width().unwrap_or(0);
+                    let fits = w <= budget;
+                    budget = budget.saturating_sub(w);
+                    fits
+                })
+                .collect::<String>();
+            s.push('…');
+            s
+        }
+        _ => s.to_string(),
+    }
+}
+
+fn pad(s: &str, width: usize) -> String {
+    format!("{s}{}", " ".repeat(width.saturating_sub(self::width(s))))
+}
+
+/// Renders aligned columns, with ANSI colours if `ansi` is set.
+fn render_columns(out: &mut String, sections: &[Section], widths: [usize; 3], ansi: bool) {
+    let [key_width, help_width, description_width] = widths;
+    let color = |color: &str, s: String| {
+        if ansi && !s.trim().is_empty() {
+            format!("{color}{s}{RESET}")
+        } else {
+            s
+        }
+    };
+
+    for (i, (group, rows)) in sections.iter().enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        if let Some(group) = group {
+            let _ = writeln!(out, "{}", color(BOLD, group.clone()));
+        }
+
+        for row in rows {
+            let mut columns = vec![color(COLOR_KEY, pad(&row.key, key_width))];
+            if help_width > 0 {
+                columns.push(color(COLOR_HELP, pad(&row.help, help_width)));
+            }
+            if description_width > 0 {
+                columns.push(color(COLOR_TEXT, row.description.clone()));
+            }
+
+            let _ = writeln!(out, "{}", columns.join("  ").trim_end());
+        }
+    }
+}
+
+fn render_markdown(out: &mut String, sections: &[Section], widths: [usize; 3]) {
+    let escape = |s: &str| s.replace('|', "\\|");
+    let [_, help_width, description_width] = widths;
+
+    let mut header = vec!["Key"];
+    if help_width > 0 {
+        header.push("Help");
+    }
+    if description_width > 0 {
+        header.push("Description");
+    }
+
+    for (i, (group, rows)) in sections.iter().enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        if let Some(group) = group {
+            let _ = writeln!(out, "### {}\n", escape(group));
+        }
+
+        let _ = writeln!(out, "| {} |", header.join(" | "));
+        let _ = writeln!(out, "|{}", " --- |".repeat(header.len()));
+
+        for row in rows {
+            let mut columns = vec![format!("`{}`", escape(&row.key))];
+            if help_width > 0 {
+                columns.push(escape(&row.help));
+            }
+            if description_width > 0 {
+                columns.push(escape(&row.description).replace('\n', "<br>"));
+            }
+
+            let _ = writeln!(out, "| {} |", columns.join(" | "));
+        }
+    }
+}
+
+fn render_html(out: &mut String, sections: &[Section], widths: [usize; 3]) {
+    let [_, help_width, description_width] = widths;
+
+    for (group, rows) in sections {
+        let _ = writeln!(out, "<table>");
+        if let Some(group) = group {
+            let _ = writeln!(out, "  <caption>{}</caption>", escape_html(group));
+        }
+
+        let _ = write!(out, "  <thead><tr><th>Key</th>");
+        if help_width > 0 {
+            let _ = write!(out, "<th>Help</th>");
+        }
+        if description_width > 0 {
+            let _ = write!(out, "<th>Description</th>");
+        }
+        let _ = writeln!(out, "</tr></thead>\n  <tbody>");
+
+        for row in rows {
+            let _ = write!(out, "    <tr><td><kbd>{}</kbd></td>", escape_html(&row.key));
+            if help_width > 0 {
+                let _ = write!(out, "<td>{}</td>", escape_html(&row.help));
+            }
+            if description_width > 0 {
+                let _ = write!(out, "<td>{}</td>", escape_html(&row.description));
+            }
+            let _ = writeln!(out, "</tr>");
+        }
+
+        let _ = writeln!(out, "  </tbody>\n</table>");
+    }
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> Config<&'static str> {
+        Config::new(vec![
+            (
+                "Quit",
+                Item::new(vec!["q".into(), "esc".into()], "Quit the app".into()),
+            ),
+            (
+                "Jump",
+                Item::new(vec!["space".into()], "Jump over obstacles".into())
+                    .with_symbol(Some("␣"))
+                    .with_help(Some("jump")),
+            ),
+            (
+                "Pipe",
+                Item::new(vec!["|".into()], "Pipe <selection> | cmd".into())
+                    .with_group(Some("Shell")),
+            ),
+            ("Disabled", Item::new(vec![], "Never shown".into())),
+        ])
+    }
+
+    #[test]
+    fn test_render_text() {
+        let text = config()
+            .cheatsheet()
+            .max_description_width(12)
+            .render(Format::Text);
+
+        assert_eq!(
+            text,
+            "\
+q, esc        Quit the app
+␣       jump  Jump over o…
+
+Shell
+|             Pipe <selec…
+"
+        );
+    }
+
+    #[test]
+    fn test_render_style() {
+        let text = config()
+            .cheatsheet()
+            .style(Style::Windows)
+            .max_description_width(12)
+            .render(Format::Text);
+
+        assert_eq!(
+            text,
+            "\
+Q, Esc        Quit the app
+␣       jump  Jump over o…
+
+Shell
+|             Pipe <selec…
+"
+        );
+    }
+
+    #[test]
+    fn test_render_grouped() {
+        let config = config();
+        let cheatsheet = config
+            .cheatsheet()
+            .max_key_width(4)
+            .group_by(|t, _| (*t != "Quit").then(|| "Game".to_string()));
+
+        assert_eq!(
+            cheatsheet.render(Format::Text),
+            "\
+q, …        Quit the app
+
+Game
+␣     jump  Jump over obstacles
+|           Pipe <selection> | cmd
+"
+        );
+
+        assert_eq!(
+            cheatsheet.render(Format::Markdown),
+            "\
+| Key | Help | Description |
+| --- | --- | --- |
+| `q, …` |  | Quit the app |
+
+### Game
+
+| Key | Help | Description |
+| --- | --- | --- |
+| `␣` | jump | Jump over obstacles |
+| `\\|` |  | Pipe <selection> \\| cmd |
+"
+        );
+    }
+
+    #[test]
+    fn test_render_item_groups() {
+        let mut config = config();
+        config.items[0].1.order = Some(1);
+        config.items[1].1.order = Some(0);
+
+        assert_eq!(
+            config.cheatsheet().render(Format::Text),
+            "\
+␣       jump  Jump over obstacles
+q, esc        Quit the app
+
+Shell
+|             Pipe <selection> | cmd
+"
+        );
+    }
+
+    #[test]
+    fn test_render_wide_chars() {
+        let config = Config::new(vec![
+            (
+                "Save",
+                Item::new(vec!["s".into()], "保存してから終了".into()).with_symbol(Some("💾")),
+            ),
+            ("Quit", Item::new(vec!["q".into()], "Quit".into())),
+        ]);
+
+        assert_eq!(
+            config
+                .cheatsheet()
+                .max_description_width(6)
+                .render(Format::Text),
+            "\
+💾  保存…
+q   Quit
+"
+        );
+    }
+
+    #[test]
+    fn test_render_html() {
+        let html = config().cheatsheet().render(Format::Html);
+
+        assert!(html.starts_with("<table>\n  <thead><tr><th>Key</th><th>Help</th>"));
+        assert!(html.contains("<td><kbd>␣</kbd></td><td>jump</td>"));
+        assert!(html.contains("<td>Pipe &lt;selection&gt; | cmd</td>"));
+        assert!(!html.contains("Never shown"));
+    }
+
+    #[test]
+    fn test_render_ansi() {
+        let ansi = config().cheatsheet().render(Format::Ansi);
+
+        assert!(ansi.starts_with(&format!("{COLOR_KEY}q, esc{RESET}")));
+        assert!(ansi.contains(&format!("{COLOR_HELP}jump{RESET}")));
+    }
+}
@@ -25,14 +25,25 @@
 //! trait-based extension points.
 //!
 //! See [`Config`], [`DerivedConfig`], and [`Item`] for more details.
-use keymap_parser::parse_seq;
+use keymap_parser::{
+    parse_seq,
+    parser::ParseError,
+    style::{Style, StyledDisplay},
+    when::{Context, Expr},
+};
 use serde::{
-    de::{MapAccess, Visitor},
-    Deserialize, Deserializer,
+    de::{self, MapAccess, Visitor},
+    ser::{SerializeMap, SerializeStruct},
+    Deserialize, Deserializer, Serialize, Serializer,
 };
-use std::{fmt, marker::PhantomData, ops::Deref};
+use std::{fmt, marker::PhantomData, ops::Deref, time::Instant};
 
-use crate::{keymap::ToKeyMap, matcher::Matcher, KeyMap};
+use crate::{
+    analysis::{self, Diagnostic},
+    keymap::ToKeyMap,
+    matcher::{Clock, Continuation, Matcher, Resolution, Resolver},
+    KeyMap,
+};
 
 /// A trait for providing a default mapping between keys and items.
 ///
@@ -149,11 +160,13 @@ pub struct Config<T> {
     /// A list of `(T, Item)` pairs as provided by deserialization.
     pub items: Vec<(T, Item)>,
 
-    /// A reverse-lookup structure: each element is `(Vec<KeyMap>, usize)`, where
-    /// `Vec<KeyMap>` is the parsed key sequence and `usize` is an index into
-    /// the `items` vector. This allows efficient lookup of `(T, Item)` by
-    /// matching against a parsed `KeyMap` sequence.
-    matcher: Matcher<usize>,
+    /// A reverse-lookup structure: each element is `(Vec<KeyMap>, Vec<usize>)`,
+    /// where `Vec<KeyMap>` is the parsed key sequence and `Vec<usize>` holds
+    /// the indices into the `items` vector bound to it, in insertion order.
+    /// This allows efficient lookup of `(T, Item)` by matching against a
+    /// parsed `KeyMap` sequence. The last index wins unless a context-aware
+    /// lookup skips items whose `when` clause does not hold.
+    matcher: Matcher<Vec<usize>>,
 }
 
 /// A configuration that merges user-provided entries with defaults.
@@ -227,6 +240,10 @@ impl<T> Deref for DerivedConfig<T> {
     }
 }
 
+/// A group of items as returned by [`Config::grouped_items`]: the group name
+/// (`None` for ungrouped items) and its `(T, Item)` pairs.
+pub type ItemGroup<'a, T> = (Option<&'a str>, Vec<(&'a T, &'a Item)>);
+
 /// Represents a single mapping entry: a list of key strings and a human-
 /// readable description. During deserialization, each string in `keys`
 /// will be parsed into a `Vec<KeyMap>` internally to build the reverse lookup.
@@ -242,8 +259,8 @@ impl<T> Deref for DerivedConfig<T> {
 /// ```ignore
 /// let item = Item::new(vec!["a".into(), "b c".into()], "Some command".into());
 /// ```
-#[derive(Debug, Deserialize, PartialEq)]
-#[serde(from = "ItemRaw")]
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(try_from = "ItemRaw")]
 pub struct Item {
     /// A collection of key expressions. Each expression will be run through
     /// `keymap_parser::parse_seq`, so special notations like `@digit` or
@@ -258,45 +275,370 @@ pub struct Item {
 
     /// A short help description of the binding (e.g. `jump`).
     pub help: Option<String>,
+
+    /// A condition (e.g. `editorFocus && !readOnly`) under which the binding
+    /// applies. Only honored by context-aware lookups such as
+    /// [`Config::get_with`].
+    pub when: Option<Expr>,
+
+    /// The category of the binding on help screens (e.g. `Navigation`).
+    /// Also accepted as `category`.
+    pub group: Option<String>,
+
+    /// A hint for sorting bindings on help screens, lowest first. Items
+    /// without one follow in config order. See [`Config::sorted_items`].
+    pub order: Option<i32>,
 }
 
 /// Raw deserialization target — Serde deserializes into this first,
-/// then [`From<ItemRaw>`] converts it into [`Item`] with fallback logic.
+/// then [`ItemRaw::merge`] converts it into [`Item`] with fallback logic.
 #[derive(Deserialize)]
-struct ItemRaw {
-    keys: Vec<String>,
+pub(crate) struct ItemRaw {
+    keys: Option<Vec<String>>,
     #[serde(default)]
     description: String,
     symbol: Option<String>,
     help: Option<String>,
+    when: Option<Expr>,
+    #[serde(alias = "category")]
+    group: Option<String>,
+    order: Option<i32>,
+    /// Keys appended to `keys` (or to the default keys when `keys` is omitted).
+    #[serde(default)]
+    add_keys: Vec<String>,
+    /// Keys removed from `keys` (or from the default keys when `keys` is omitted).
+    #[serde(default, alias = "unbind")]
+    remove_keys: Vec<String>,
+    /// Fields reset instead of falling back to the default item.
+    #[serde(default)]
+    clear: Vec<Field>,
 }
 
-impl From<ItemRaw> for Item {
-    fn from(raw: ItemRaw) -> Self {
-        let symbol = raw.symbol.or_else(|| raw.keys.first().cloned());
-        Self {
-            keys: raw.keys,
-            description: raw.description,
+/// An optional field of an [`Item`], as listed in `clear = ["help"]` to reset
+/// it rather than keep the default value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Field {
+    Description,
+    Symbol,
+    Help,
+    When,
+    Group,
+    Order,
+}
+
+impl Field {
+    /// The names accepted in `clear`.
+    #[cfg(feature = "schema")]
+    pub(crate) const NAMES: [&'static str; 6] =
+        ["description", "symbol", "help", "when", "group", "order"];
+}
+
+impl ItemRaw {
+    /// Converts the raw entry into an [`Item`], filling omitted fields from
+    /// the `default` item (if any).
+    ///
+    /// - `keys` falls back to the default keys. `remove_keys` are then removed
+    ///   and `add_keys` appended.
+    /// - `description`, `help`, `when`, `group` and `order` fall back to the
+    ///   default values.
+    /// - `symbol` falls back to the default symbol if the first key is
+    ///   unchanged, otherwise to the first key.
+    /// - Fields listed in `clear` are reset (and `symbol` left unset) instead
+    ///   of falling back.
+    fn merge(self, default: Option<&Item>) -> Result<Item, &'static str> {
+        let mut keys = match (self.keys, default) {
+            (Some(keys), _) => keys,
+            (None, Some(default)) => default.keys.clone(),
+            (None, None) if !self.add_keys.is_empty() => Vec::new(),
+            (None, None) => return Err("missing field `keys`"),
+        };
+        keys.retain(|key| {
+            !self
+                .remove_keys
+                .iter()
+                .any(|remove| is_same_key(remove, key))
+        });
+        for key in self.add_keys {
+            if !keys.iter().any(|existing| is_same_key(existing, &key)) {
+                keys.push(key);
+            }
+        }
+
+        let clear = self.clear;
+        let inherit = |field| default.filter(|_| !clear.contains(&field));
+
+        let description = match inherit(Field::Description) {
+            Some(default) if self.description.is_empty() => default.description.clone(),
+            _ => self.description,
+        };
+        let help = self
+            .help
+            .or_else(|| inherit(Field::Help).and_then(|default| default.help.clone()));
+        let when = self
+            .when
+            .or_else(|| inherit(Field::When).and_then(|default| default.when.clone()));
+        let group = self
+            .group
+            .or_else(|| inherit(Field::Group).and_then(|default| default.group.clone()));
+        let order = self
+            .order
+            .or_else(|| inherit(Field::Order).and_then(|default| default.order));
+        let symbol = self.symbol.or_else(|| match default {
+            _ if clear.contains(&Field::Symbol) => None,
+            Some(default) if default.keys.first() == keys.first() => default.symbol.clone(),
+            _ => keys.first().cloned(),
+        });
+
+        Ok(Item {
+            keys,
+            description,
             symbol,
-            help: raw.help,
+            help,
+            when,
+            group,
+            order,
+        })
+    }
+}
+
+impl TryFrom<ItemRaw> for Item {
+    type Error = &'static str;
+
+    fn try_from(raw: ItemRaw) -> Result<Self, Self::Error> {
+        raw.merge(None)
+    }
+}
+
+/// Returns `true` if both key expressions parse to the same sequence
+/// (e.g. `"del"` and `"delete"`), or are equal strings otherwise.
+pub(crate) fn is_same_key(a: &str, b: &str) -> bool {
+    match (parse_seq(a), parse_seq(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// A user entry of a [`DerivedConfig<T>`] map: either a boolean that keeps
+/// (`true`) or disables (`false`) the default item, or a (partial) item.
+pub(crate) enum EntryRaw {
+    Enabled(bool),
+    Item(Box<ItemRaw>),
+}
+
+impl EntryRaw {
+    /// Applies the entry to the `default` item.
+    ///
+    /// Returns `None` if the entry keeps the default as is (or disables an
+    /// item without a default).
+    pub(crate) fn merge(self, default: Option<&Item>) -> Result<Option<Item>, &'static str> {
+        match (self, default) {
+            // Keep the default Item as is
+            (EntryRaw::Enabled(true), _) | (EntryRaw::Enabled(false), None) => Ok(None),
+            // Disable the default Item by unbinding all of its keys
+            (EntryRaw::Enabled(false), Some(default)) => Ok(Some(Item {
+                keys: Vec::new(),
+                symbol: None,
+                ..default.clone()
+            })),
+            (EntryRaw::Item(raw), default) => raw.merge(default).map(Some),
         }
     }
 }
 
+/// Applies a user entry to the default `items`: the item of `t` is replaced or
+/// disabled, or appended if there is no default.
+pub(crate) fn merge_entry<T: PartialEq>(
+    items: &mut Vec<(T, Item)>,
+    t: T,
+    entry: EntryRaw,
+) -> Result<(), &'static str> {
+    let pos = items
+        .iter()
+        .position(|(existing_key, _)| existing_key == &t);
+    let default = pos.map(|pos| &items[pos].1);
+
+    let Some(item) = entry.merge(default)? else {
+        return Ok(());
+    };
+
+    match pos {
+        // Override the default Item if the key matches
+        Some(pos) => items[pos].1 = item,
+        // Append a new entry
+        None => items.push((t, item)),
+    }
+
+    Ok(())
+}
+
+impl<'de> Deserialize<'de> for EntryRaw {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct EntryVisitor;
+
+        impl<'de> Visitor<'de> for EntryVisitor {
+            type Value = EntryRaw;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a boolean or an item")
+            }
+
+            fn visit_bool<E>(self, value: bool) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(EntryRaw::Enabled(value))
+            }
+
+            fn visit_map<M>(self, map: M) -> Result<Self::Value, M::Error>
+            where
+                M: MapAccess<'de>,
+            {
+                ItemRaw::deserialize(de::value::MapAccessDeserializer::new(map))
+                    .map(|raw| EntryRaw::Item(Box::new(raw)))
+            }
+        }
+
+        deserializer.deserialize_any(EntryVisitor)
+    }
+}
+
+impl<T> TryFrom<Vec<(T, Item)>> for Config<T> {
+    type Error = ConfigError;
+
+    fn try_from(items: Vec<(T, Item)>) -> Result<Self, Self::Error> {
+        Self::try_new(items)
+    }
+}
+
+/// An error returned when building a [`Config`] from items whose key
+/// expressions fail to parse.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigError {
+    /// Every key expression that failed to parse, in item order.
+    pub invalid_keys: Vec<InvalidKey>,
+}
+
+/// A key expression in an [`Item`] that failed to parse.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvalidKey {
+    /// The index of the item within the list of `(T, Item)` pairs.
+    pub index: usize,
+    /// The key expression as written (e.g. `"enter2"`).
+    pub key: String,
+    /// The error returned by the parser.
+    pub error: ParseError,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, invalid) in self.invalid_keys.iter().enumerate() {
+            if i > 0 {
+                f.write_str("; ")?;
+            }
+            write!(f, "{invalid}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl fmt::Display for InvalidKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid key \"{}\" (item {}): {}",
+            self.key, self.index, self.error
+        )
+    }
+}
+
+/// A key sequence bound to an item, as returned by [`Config::keys_for`].
+///
+/// Its [`Display`](fmt::Display) form is the canonical key expression (e.g.
+/// `"delete"` for a key written as `"del"`), suitable for status bars and
+/// menus.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeySequence<'a> {
+    /// The key expression as written in [`Item::keys`].
+    pub key: &'a str,
+    /// The parsed key sequence.
+    pub nodes: Vec<KeyMap>,
+}
+
+impl fmt::Display for KeySequence<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, node) in self.nodes.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{node}")?;
+        }
+
+        Ok(())
+    }
+}
+
 impl<T> Config<T> {
+    /// Creates a new `Config` from the given `(T, Item)` pairs.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any key expression fails to parse. Use [`Config::try_new`]
+    /// when the items come from runtime data.
     pub fn new(items: Vec<(T, Item)>) -> Self {
-        let mut matcher = Matcher::new();
+        Self::try_new(items).unwrap_or_else(|err| panic!("{err}"))
+    }
 
-        items.iter().enumerate().for_each(|(index, (_, item))| {
-            item.keys
-                .iter()
-                .map(|keys| parse_seq(keys).expect("a valid key"))
-                .for_each(|keys| {
-                    matcher.add(keys, index);
-                });
-        });
+    /// Creates a new `Config` from the given `(T, Item)` pairs, returning a
+    /// [`ConfigError`] listing every key expression that fails to parse.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use keymap::{Config, Item};
+    /// let result = Config::try_new(vec![
+    ///     ("Create", Item::new(vec!["c".into()], "Create".into())),
+    ///     ("Delete", Item::new(vec!["d".into(), "enter2".into()], "Delete".into())),
+    /// ]);
+    ///
+    /// let err = result.unwrap_err();
+    /// assert_eq!(err.invalid_keys[0].index, 1);
+    /// assert_eq!(err.invalid_keys[0].key, "enter2");
+    /// ```
+    pub fn try_new(items: Vec<(T, Item)>) -> Result<Self, ConfigError> {
+        let mut patterns: Vec<(Vec<KeyMap>, Vec<usize>)> = Vec::new();
+        let mut invalid_keys = Vec::new();
+
+        for (index, (_, item)) in items.iter().enumerate() {
+            for key in &item.keys {
+                match parse_seq(key) {
+                    Ok(keys) => match patterns.iter_mut().find(|(p, _)| *p == keys) {
+                        Some((_, indices)) if indices.last() == Some(&index) => {}
+                        Some((_, indices)) => indices.push(index),
+                        None => patterns.push((keys, vec![index])),
+                    },
+                    Err(error) => invalid_keys.push(InvalidKey {
+                        index,
+                        key: key.clone(),
+                        error,
+                    }),
+                }
+            }
+        }
+
+        if !invalid_keys.is_empty() {
+            return Err(ConfigError { invalid_keys });
+        }
 
-        Self { items, matcher }
+        let matcher = patterns.into_iter().collect();
+
+        Ok(Self { items, matcher })
     }
 
     /// Retrieve just the key type `T` (without the `Item`) `KeyEvent`.
@@ -410,11 +752,15 @@ impl<T> Config<T> {
     /// Lookup an `(T, Item)` pair by an entire slice of parsed [`type@KeyMap`]s.
     /// This performs an exact match against one of the stored `Vec<KeyMap>`.
     ///
+    /// Like every lookup without a context, this skips items with a
+    /// [`Item::when`] clause. Use [`Config::get_item_by_keymaps_with`] to
+    /// evaluate them.
+    ///
     /// # Example
     ///
     /// ```
     /// # use keymap::{Config, Item};
-    /// # use keymap_parser::parse_seq;
+    /// # use keymap_parser::{parse_seq, parser::ParseError};
     /// let config: Config<String> = toml::from_str(r#"
     ///     Create = { keys = ["x y"], description = "Create a new item" }
     /// "#).unwrap();
@@ -426,8 +772,22 @@ impl<T> Config<T> {
     /// ```
     pub fn get_item_by_keymaps(&self, keys: &[KeyMap]) -> Option<(&T, &Item)> {
         self.matcher
-            .get(keys)
-            .map(|i| (&self.items[*i].0, &self.items[*i].1))
+            .get_by(keys, |indices| self.entry(indices).is_some())
+            .and_then(|indices| self.entry(indices))
+    }
+
+    /// Like [`Config::get_item_by_keymaps`], but skips the items whose key
+    /// type `T` does not pass `filter`, falling back to the next match.
+    pub(crate) fn get_item_by_keymaps_by<F: Fn(&T) -> bool>(
+        &self,
+        keys: &[KeyMap],
+        filter: F,
+    ) -> Option<(&T, &Item)> {
+        let entry =
+            |indices: &[usize]| self.entry_by(indices, |t, item| item.when.is_none() && filter(t));
+        self.matcher
+            .get_by(keys, |indices| entry(indices).is_some())
+            .and_then(|indices| entry(indices))
     }
 
     /// Lookup an `(T, Item)` pair by an entire slice of parsed [`type@KeyMap`]s,
@@ -436,9 +796,8 @@ impl<T> Config<T> {
     where
         T: KeyMapConfig<T> + Clone,
     {
-        self.matcher
-            .get(keys)
-            .map(|i| (self.items[*i].0.bind(keys), &self.items[*i].1))
+        self.get_item_by_keymaps(keys)
+            .map(|(t, item)| (t.bind(keys), item))
     }
 
     /// Lookup an `(T, Item)` by a raw string. This will attempt to parse the
@@ -462,6 +821,672 @@ impl<T> Config<T> {
     pub fn get_item_by_key_str(&self, key: &str) -> Option<(&T, &Item)> {
         self.get_item_by_keymaps(parse_seq(key).ok()?.as_slice())
     }
+
+    /// Retrieve the key type `T` bound to the given key event, skipping items
+    /// whose [`Item::when`] clause does not hold in `context`.
+    ///
+    /// When several items share a key, the last one whose clause holds (or
+    /// that has none) wins.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use keymap::Config;
+    /// # use keymap_parser::parse;
+    /// let config: Config<String> = toml::from_str(r#"
+    ///     Newline = { keys = ["enter"] }
+    ///     Submit = { keys = ["enter"], when = "inputFocus && !multiline" }
+    /// "#).unwrap();
+    ///
+    /// let enter = parse("enter").unwrap();
+    /// assert_eq!(config.get_with(&enter, &["inputFocus"][..]).unwrap(), "Submit");
+    /// assert_eq!(config.get_with(&enter, &["inputFocus", "multiline"][..]).unwrap(), "Newline");
+    /// ```
+    pub fn get_with<K: ToKeyMap, C: Context + ?Sized>(&self, key: &K, context: &C) -> Option<&T> {
+        self.get_item_with(key, context).map(|(t, _)| t)
+    }
+
+    /// Retrieve the `(T, Item)` pair bound to the given key event in
+    /// `context`. See [`Config::get_with`].
+    pub fn get_item_with<K: ToKeyMap, C: Context + ?Sized>(
+        &self,
+        key: &K,
+        context: &C,
+    ) -> Option<(&T, &Item)> {
+        self.get_item_by_keymaps_with(&[key.to_keymap().ok()?], context)
+    }
+
+    /// Retrieve the key type `T` bound to a sequence of key events in
+    /// `context`. See [`Config::get_with`].
+    pub fn get_seq_with<K: ToKeyMap, C: Context + ?Sized>(
+        &self,
+        keys: &[K],
+        context: &C,
+    ) -> Option<&T> {
+        let nodes = keys
+            .iter()
+            .map(|key| key.to_keymap().ok())
+            .collect::<Option<Vec<_>>>()?;
+
+        self.get_item_by_keymaps_with(&nodes, context)
+            .map(|(t, _)| t)
+    }
+
+    /// Lookup an `(T, Item)` pair by a slice of parsed [`type@KeyMap`]s in
+    /// `context`. See [`Config::get_with`].
+    pub fn get_item_by_keymaps_with<C: Context + ?Sized>(
+        &self,
+        keys: &[KeyMap],
+        context: &C,
+    ) -> Option<(&T, &Item)> {
+        self.matcher
+            .get_by(keys, |indices| self.entry_with(indices, context).is_some())
+            .and_then(|indices| self.entry_with(indices, context))
+    }
+
+    /// Like [`Config::get_item_by_keymaps_with`], but skips the items whose
+    /// key type `T` does not pass `filter`, falling back to the next match.
+    pub(crate) fn get_item_by_keymaps_with_by<C: Context + ?Sized, F: Fn(&T) -> bool>(
+        &self,
+        keys: &[KeyMap],
+        context: &C,
+        filter: F,
+    ) -> Option<(&T, &Item)> {
+        let entry = |indices: &[usize]| {
+            self.entry_by(indices, |t, item| item.applies_in(context) && filter(t))
+        };
+        self.matcher
+            .get_by(keys, |indices| entry(indices).is_some())
+            .and_then(|indices| entry(indices))
+    }
+
+    /// Returns the key sequences currently bound to `value`, in the order of
+    /// [`Item::keys`].
+    ///
+    /// Keys taken over by a later item with the same sequence (and no `when`
+    /// clause) are left out, as are duplicates such as `"del"` and `"delete"`.
+    /// Since this reads the merged config, the result reflects user overrides,
+    /// unlike [`KeyMapConfig::keymap_item`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use keymap::Config;
+    /// let config: Config<String> = toml::from_str(r#"
+    ///     Save = { keys = ["ctrl-s", "ctrl-x ctrl-s"] }
+    /// "#).unwrap();
+    ///
+    /// let keys = config.keys_for(&"Save".to_string());
+    /// assert_eq!(keys[0].to_string(), "ctrl-s");
+    /// assert_eq!(keys[1].nodes.len(), 2);
+    /// ```
+    pub fn keys_for(&self, value: &T) -> Vec<KeySequence<'_>>
+    where
+        T: PartialEq,
+    {
+        let Some(index) = self.items.iter().position(|(t, _)| t == value) else {
+            return Vec::new();
+        };
+
+        let mut sequences: Vec<KeySequence> = Vec::new();
+        for key in &self.items[index].1.keys {
+            let Ok(nodes) = parse_seq(key) else {
+                continue;
+            };
+
+            let duplicate = sequences.iter().any(|seq| seq.nodes == nodes);
+            let shadowed = self.items[index + 1..].iter().any(|(_, item)| {
+                item.when.is_none()
+                    && item
+                        .keys
+                        .iter()
+                        .any(|k| parse_seq(k).is_ok_and(|other| other == nodes))
+            });
+
+            if !duplicate && !shadowed {
+                sequences.push(KeySequence { key, nodes });
+            }
+        }
+
+        sequences
+    }
+
+    /// Returns the first key sequence currently bound to `value`, e.g. to
+    /// show next to a menu entry. See [`Config::keys_for`].
+    pub fn primary_key_for(&self, value: &T) -> Option<KeySequence<'_>>
+    where
+        T: PartialEq,
+    {
+        self.keys_for(value).into_iter().next()
+    }
+
+    /// Returns all items sorted by [`Item::order`], lowest first. Items
+    /// without an order follow in config order.
+    pub fn sorted_items(&self) -> Vec<(&T, &Item)> {
+        let mut items = self
+            .items
+            .iter()
+            .map(|(t, item)| (t, item))
+            .collect::<Vec<_>>();
+        items.sort_by_key(|(_, item)| (item.order.is_none(), item.order));
+        items
+    }
+
+    /// Returns the items grouped by [`Item::group`], each group sorted as in
+    /// [`Config::sorted_items`].
+    ///
+    /// Ungrouped items (`None`) come first, then groups in order of first
+    /// appearance.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use keymap::Config;
+    /// let config: Config<String> = toml::from_str(r#"
+    ///     Down = { keys = ["j"], group = "Navigation", order = 2 }
+    ///     Up = { keys = ["k"], group = "Navigation", order = 1 }
+    ///     Undo = { keys = ["u"], category = "Editing" }
+    /// "#).unwrap();
+    ///
+    /// for (group, items) in config.grouped_items() {
+    ///     println!("{}", group.unwrap_or("General"));
+    ///     for (action, item) in items {
+    ///         println!("  {} {action}", item.keys.join(", "));
+    ///     }
+    /// }
+    /// ```
+    pub fn grouped_items(&self) -> Vec<ItemGroup<'_, T>> {
+        let mut groups: Vec<ItemGroup<'_, T>> = vec![(None, Vec::new())];
+
+        for (t, item) in self.sorted_items() {
+            let group = item.group.as_deref();
+            match groups.iter_mut().find(|(name, _)| *name == group) {
+                Some((_, items)) => items.push((t, item)),
+                None => groups.push((group, vec![(t, item)])),
+            }
+        }
+
+        groups.retain(|(_, items)| !items.is_empty());
+        groups
+    }
+
+    /// Reports conflicting or shadowed bindings, such as duplicate patterns,
+    /// exact keys shadowing key groups, overlapping key groups, and bindings
+    /// that are a prefix of a longer sequence.
+    ///
+    /// Bindings gated by `when` clauses are reported unless the clauses are
+    /// exclusive (e.g. `a` and `!a`). See [`Diagnostic::when`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use keymap::{Config, Diagnostic};
+    /// let config: Config<String> = toml::from_str(r#"
+    ///     Delete = { keys = ["d"] }
+    ///     DeleteLine = { keys = ["d d"] }
+    /// "#).unwrap();
+    ///
+    /// for diagnostic in config.analyze() {
+    ///     println!("warning: {diagnostic}");
+    /// }
+    /// ```
+    pub fn analyze(&self) -> Vec<Diagnostic<'_, T>> {
+        analysis::analyze(&self.items)
+    }
+
+    /// Lists every key that may follow `prefix`, together with the `(T, Item)`
+    /// it would complete and whether it leads to a deeper prefix.
+    ///
+    /// This is useful for drawing a "which-key" style popup while a sequence
+    /// is pending (e.g. by passing [`Resolver::pending`]). Next keys may be key
+    /// groups such as `@digit`.
+    ///
+    /// Like [`Config::resolve`], this skips items with a [`Item::when`]
+    /// clause, so keys bound only by such items are not listed. Use
+    /// [`Config::continuations_with`] to evaluate them.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use keymap::Config;
+    /// # use keymap_parser::{parse_seq, parser::ParseError};
+    /// let config: Config<String> = toml::from_str(r#"
+    ///     Next = { keys = ["ctrl-b n"], description = "Next window" }
+    ///     Select = { keys = ["ctrl-b @digit"], description = "Select window" }
+    /// "#).unwrap();
+    ///
+    /// for next in config.continuations(&parse_seq("ctrl-b").unwrap()) {
+    ///     if let Some((_, item)) = next.value {
+    ///         println!("{} {}", next.node, item.description);
+    ///     }
+    /// }
+    /// ```
+    pub fn continuations(&self, prefix: &[KeyMap]) -> Vec<Continuation<(&T, &Item)>> {
+        self.continuations_by(prefix, |indices| self.entry(indices))
+    }
+
+    /// Lists every key that may follow `prefix` in `context`, skipping items
+    /// whose [`Item::when`] clause does not hold. See [`Config::continuations`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use keymap::Config;
+    /// # use keymap_parser::parse_seq;
+    /// let config: Config<String> = toml::from_str(r#"
+    ///     Next = { keys = ["ctrl-b n"] }
+    ///     Zoom = { keys = ["ctrl-b z"], when = "multiPane" }
+    /// "#).unwrap();
+    ///
+    /// let prefix = parse_seq("ctrl-b").unwrap();
+    /// assert_eq!(config.continuations_with(&prefix, &["multiPane"][..]).len(), 2);
+    /// assert_eq!(config.continuations_with(&prefix, &["other"][..]).len(), 1);
+    /// ```
+    pub fn continuations_with<C: Context + ?Sized>(
+        &self,
+        prefix: &[KeyMap],
+        context: &C,
+    ) -> Vec<Continuation<(&T, &Item)>> {
+        self.continuations_by(prefix, |indices| self.entry_with(indices, context))
+    }
+
+    /// Lists the continuations of `prefix`, counting only the patterns for
+    /// which `entry` picks an item.
+    fn continuations_by<'a>(
+        &'a self,
+        prefix: &[KeyMap],
+        entry: impl Fn(&[usize]) -> Option<(&'a T, &'a Item)>,
+    ) -> Vec<Continuation<(&'a T, &'a Item)>> {
+        self.matcher
+            .continuations_by(prefix, |indices| entry(indices).is_some())
+            .into_iter()
+            .map(|next| Continuation {
+                node: next.node,
+                value: next.value.and_then(|indices| entry(indices)),
+                is_prefix: next.is_prefix,
+            })
+            .collect()
+    }
+
+    /// Feeds a single key event into `resolver`, matching multi-key sequences
+    /// incrementally as keys arrive.
+    ///
+    /// Returns the [`Resolution`]s produced by this key. A key that cannot be
+    /// converted into a [`type@KeyMap`] resets the resolver and yields
+    /// [`Resolution::NoMatch`].
+    ///
+    /// Without a context, items with a [`Item::when`] clause are skipped as
+    /// in [`Config::get`]: sequences bound only by such items neither match
+    /// nor keep the resolver pending. Use [`Config::resolve_with`] to
+    /// evaluate `when` clauses.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use keymap::{Config, Resolution, Resolver};
+    /// # use keymap_parser::parse;
+    /// let config: Config<String> = toml::from_str(r#"
+    ///     Delete = { keys = ["d"], description = "Delete a char" }
+    ///     DeleteLine = { keys = ["d d"], description = "Delete a line" }
+    /// "#).unwrap();
+    ///
+    /// let mut resolver = Resolver::new();
+    /// let d = parse("d").unwrap();
+    ///
+    /// // `d` alone is ambiguous, so the resolver waits for the next key.
+    /// assert_eq!(config.resolve_keymap(&mut resolver, d.clone()), vec![Resolution::Pending]);
+    ///
+    /// let resolutions = config.resolve_keymap(&mut resolver, d);
+    /// assert!(matches!(&resolutions[..], [Resolution::Matched(action, _)] if *action == "DeleteLine"));
+    /// ```
+    pub fn resolve<K: ToKeyMap, C: Clock>(
+        &self,
+        resolver: &mut Resolver<C>,
+        key: &K,
+    ) -> Vec<Resolution<&T>> {
+        match key.to_keymap() {
+            Ok(node) => self.resolve_keymap(resolver, node),
+            Err(_) => {
+                resolver.reset();
+                vec![Resolution::NoMatch]
+            }
+        }
+    }
+
+    /// Feeds a single parsed `KeyMap` into `resolver`. See [`Config::resolve`].
+    pub fn resolve_keymap<C: Clock>(
+        &self,
+        resolver: &mut Resolver<C>,
+        node: KeyMap,
+    ) -> Vec<Resolution<&T>> {
+        let entry = |indices: &[usize]| self.entry(indices);
+        self.lookup(
+            resolver.feed_by(&self.matcher, node, |indices| entry(indices).is_some()),
+            entry,
+        )
+    }
+
+    /// Expires the pending sequence in `resolver` if its timeout has passed at
+    /// `now`, emitting the shorter binding (e.g. `d` when `d d` never arrived).
+    ///
+    /// See [`Resolver::poll`] and [`Resolver::with_timeout`].
+    pub fn poll<C: Clock>(&self, resolver: &mut Resolver<C>, now: Instant) -> Vec<Resolution<&T>> {
+        let entry = |indices: &[usize]| self.entry(indices);
+        self.lookup(
+            resolver.poll_by(&self.matcher, now, |indices| entry(indices).is_some()),
+            entry,
+        )
+    }
+
+    /// Resolves the pending sequence in `resolver` immediately, without
+    /// waiting for its timeout. See [`Resolver::flush`].
+    pub fn flush<C: Clock>(&self, resolver: &mut Resolver<C>) -> Vec<Resolution<&T>> {
+        let entry = |indices: &[usize]| self.entry(indices);
+        self.lookup(
+            resolver.flush_by(&self.matcher, |indices| entry(indices).is_some()),
+            entry,
+        )
+    }
+
+    /// Feeds a single key event into `resolver`, skipping items whose
+    /// [`Item::when`] clause does not hold in `context`.
+    ///
+    /// Pass the same context to [`Config::poll_with`] and
+    /// [`Config::flush_with`] while the sequence is pending. See
+    /// [`Config::resolve`] and [`Config::get_with`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use keymap::{Config, Resolution, Resolver};
+    /// # use keymap_parser::parse;
+    /// let config: Config<String> = toml::from_str(r#"
+    ///     Insert = { keys = ["@any"] }
+    ///     Submit = { keys = ["enter"], when = "inputFocus" }
+    /// "#).unwrap();
+    ///
+    /// let mut resolver = Resolver::new();
+    /// let enter = parse("enter").unwrap();
+    ///
+    /// let resolutions = config.resolve_keymap_with(&mut resolver, enter.clone(), &["inputFocus"][..]);
+    /// assert!(matches!(&resolutions[..], [Resolution::Matched(action, _)] if *action == "Submit"));
+    ///
+    /// let resolutions = config.resolve_keymap_with(&mut resolver, enter, &["other"][..]);
+    /// assert!(matches!(&resolutions[..], [Resolution::Matched(action, _)] if *action == "Insert"));
+    /// ```
+    pub fn resolve_with<K: ToKeyMap, C: Clock, X: Context + ?Sized>(
+        &self,
+        resolver: &mut Resolver<C>,
+        key: &K,
+        context: &X,
+    ) -> Vec<Resolution<&T>> {
+        match key.to_keymap() {
+            Ok(node) => self.resolve_keymap_with(resolver, node, context),
+            Err(_) => {
+                resolver.reset();
+                vec![Resolution::NoMatch]
+            }
+        }
+    }
+
+    /// Feeds a single parsed `KeyMap` into `resolver` in `context`. See
+    /// [`Config::resolve_with`].
+    pub fn resolve_keymap_with<C: Clock, X: Context + ?Sized>(
+        &self,
+        resolver: &mut Resolver<C>,
+        node: KeyMap,
+        context: &X,
+    ) -> Vec<Resolution<&T>> {
+        let entry = |indices: &[usize]| self.entry_with(indices, context);
+        self.lookup(
+            resolver.feed_by(&self.matcher, node, |indices| entry(indices).is_some()),
+            entry,
+        )
+    }
+
+    /// Like [`Config::poll`], but evaluates `when` clauses in `context`.
+    pub fn poll_with<C: Clock, X: Context + ?Sized>(
+        &self,
+        resolver: &mut Resolver<C>,
+        now: Instant,
+        context: &X,
+    ) -> Vec<Resolution<&T>> {
+        let entry = |indices: &[usize]| self.entry_with(indices, context);
+        self.lookup(
+            resolver.poll_by(&self.matcher, now, |indices| entry(indices).is_some()),
+            entry,
+        )
+    }
+
+    /// Like [`Config::flush`], but evaluates `when` clauses in `context`.
+    pub fn flush_with<C: Clock, X: Context + ?Sized>(
+        &self,
+        resolver: &mut Resolver<C>,
+        context: &X,
+    ) -> Vec<Resolution<&T>> {
+        let entry = |indices: &[usize]| self.entry_with(indices, context);
+        self.lookup(
+            resolver.flush_by(&self.matcher, |indices| entry(indices).is_some()),
+            entry,
+        )
+    }
+
+    /// Maps resolved item indices back to their key type `T`, picking the
+    /// winning item with `entry`.
+    fn lookup<'a>(
+        &'a self,
+        resolutions: Vec<Resolution<&'a Vec<usize>>>,
+        entry: impl Fn(&'a [usize]) -> Option<(&'a T, &'a Item)>,
+    ) -> Vec<Resolution<&'a T>> {
+        resolutions
+            .into_iter()
+            .map(|resolution| match resolution {
+                Resolution::Matched(indices, nodes) => match entry(indices) {
+                    Some((t, _)) => Resolution::Matched(t, nodes),
+                    None => Resolution::NoMatch,
+                },
+                Resolution::Pending => Resolution::Pending,
+                Resolution::NoMatch => Resolution::NoMatch,
+            })
+            .collect()
+    }
+
+    /// Returns the item that wins for a matched pattern without a context,
+    /// i.e. the last one bound to it without a `when` clause.
+    fn entry(&self, indices: &[usize]) -> Option<(&T, &Item)> {
+        self.entry_by(indices, |_, item| item.when.is_none())
+    }
+
+    /// Returns the item that wins for a matched pattern in `context`, i.e.
+    /// the last one whose `when` clause holds (or that has none).
+    fn entry_with<C: Context + ?Sized>(
+        &self,
+        indices: &[usize],
+        context: &C,
+    ) -> Option<(&T, &Item)> {
+        self.entry_by(indices, |_, item| item.applies_in(context))
+    }
+
+    /// Returns the last item of a matched pattern that passes `filter`.
+    fn entry_by<F: Fn(&T, &Item) -> bool>(
+        &self,
+        indices: &[usize],
+        filter: F,
+    ) -> Option<(&T, &Item)> {
+        indices
+            .iter()
+            .rev()
+            .map(|&i| (&self.items[i].0, &self.items[i].1))
+            .find(|(t, item)| filter(t, item))
+    }
+}
+
+impl<T> DerivedConfig<T>
+where
+    T: KeyMapConfig<T> + PartialEq + Clone,
+{
+    /// Returns only the entries that differ from the defaults in
+    /// `T::keymap_config()`, i.e. overridden or added items.
+    ///
+    /// When serialized, each entry only lists the fields that differ from its
+    /// default, so reading the result back as a [`DerivedConfig<T>`] restores
+    /// the current items while picking up any defaults changed or added in
+    /// later releases. See [`Overrides`].
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let config: DerivedConfig<Action> = toml::from_str(&data)?;
+    ///
+    /// // Only write what the user changed
+    /// let data = toml::to_string(&config.overrides())?;
+    /// ```
+    pub fn overrides(&self) -> Overrides<T> {
+        let defaults = T::keymap_config();
+
+        let (items, defaults) = self
+            .items
+            .iter()
+            .filter_map(|(t, item)| {
+                let default = defaults
+                    .items
+                    .iter()
+                    .find(|(d, _)| d == t)
+                    .map(|(_, default)| default);
+                if default == Some(item) {
+                    return None;
+                }
+
+                Some(((t.clone(), item.clone()), default.cloned()))
+            })
+            .unzip();
+
+        Overrides { items, defaults }
+    }
+}
+
+/// The entries of a [`DerivedConfig<T>`] that differ from their defaults, as
+/// returned by [`DerivedConfig::overrides`].
+///
+/// Serializes to the format read by [`DerivedConfig<T>`], writing for each
+/// entry only what differs from its default:
+///
+/// ```toml
+/// # Only the description changed, so the default keys are still followed
+/// Create = { description = "New file" }
+/// # All keys unbound
+/// Update = false
+/// # The default help and group are removed
+/// Delete = { keys = ["x"], clear = ["help", "group"] }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Overrides<T> {
+    /// The overridden or added items, as they are in the derived config.
+    pub items: Vec<(T, Item)>,
+    /// The default of each item, if any.
+    defaults: Vec<Option<Item>>,
+}
+
+impl<T: Serialize> Serialize for Overrides<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(self.items.len()))?;
+        for ((t, item), default) in self.items.iter().zip(&self.defaults) {
+            match default {
+                Some(default) => map.serialize_entry(t, &ItemOverride { item, default })?,
+                None => map.serialize_entry(t, item)?,
+            }
+        }
+
+        map.end()
+    }
+}
+
+/// An [`Item`] serialized as the changes to its `default`.
+struct ItemOverride<'a> {
+    item: &'a Item,
+    default: &'a Item,
+}
+
+impl Serialize for ItemOverride<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let (item, default) = (self.item, self.default);
+
+        // Unbinding all keys is written as `false`, as long as nothing else changed
+        let disabled = Item {
+            keys: Vec::new(),
+            symbol: None,
+            ..default.clone()
+        };
+        if !default.keys.is_empty() && *item == disabled {
+            return serializer.serialize_bool(false);
+        }
+
+        // The symbol filled in on reading when it is omitted
+        let symbol = if item.keys.first() == default.keys.first() {
+            default.symbol.as_ref()
+        } else {
+            item.keys.first()
+        };
+
+        let mut clear = Vec::new();
+        let mut state = serializer.serialize_struct("Item", 8)?;
+
+        if item.keys != default.keys {
+            state.serialize_field("keys", &item.keys)?;
+        } else {
+            state.skip_field("keys")?;
+        }
+
+        if item.description == default.description {
+            state.skip_field("description")?;
+        } else if item.description.is_empty() {
+            state.skip_field("description")?;
+            clear.push(Field::Description);
+        } else {
+            state.serialize_field("description", &item.description)?;
+        }
+
+        match &item.symbol {
+            value if value.as_ref() == symbol => state.skip_field("symbol")?,
+            Some(value) => state.serialize_field("symbol", value)?,
+            None => {
+                state.skip_field("symbol")?;
+                clear.push(Field::Symbol);
+            }
+        }
+
+        macro_rules! field {
+            ($name:ident, $field:expr) => {
+                match &item.$name {
+                    value if *value == default.$name => state.skip_field(stringify!($name))?,
+                    Some(value) => state.serialize_field(stringify!($name), value)?,
+                    None => {
+                        state.skip_field(stringify!($name))?;
+                        clear.push($field);
+                    }
+                }
+            };
+        }
+
+        field!(help, Field::Help);
+        field!(when, Field::When);
+        field!(group, Field::Group);
+        field!(order, Field::Order);
+
+        if clear.is_empty() {
+            state.skip_field("clear")?;
+        } else {
+            state.serialize_field("clear", &clear)?;
+        }
+
+        state.end()
+    }
 }
 
 impl Item {
@@ -486,6 +1511,9 @@ impl Item {
             description,
             symbol,
             help: None,
+            when: None,
+            group: None,
+            order: None,
         }
     }
 
@@ -500,6 +1528,157 @@ impl Item {
         self.help = help.map(Into::into);
         self
     }
+
+    /// Sets the condition under which the binding applies.
+    pub fn with_when(mut self, when: Option<Expr>) -> Self {
+        self.when = when;
+        self
+    }
+
+    /// Sets the category shown on help screens.
+    pub fn with_group<S: Into<String>>(mut self, group: Option<S>) -> Self {
+        self.group = group.map(Into::into);
+        self
+    }
+
+    /// Sets the sorting hint used on help screens.
+    pub fn with_order(mut self, order: Option<i32>) -> Self {
+        self.order = order;
+        self
+    }
+
+    /// Returns `true` if the `when` clause holds in `context`, or there is
+    /// none.
+    pub(crate) fn applies_in<C: Context + ?Sized>(&self, context: &C) -> bool {
+        self.when.as_ref().is_none_or(|when| when.eval(context))
+    }
+
+    /// Returns the keys formatted in `style` (e.g. `"⌃⇧F"` for
+    /// `"ctrl-shift-f"` in [`Style::Mac`]).
+    ///
+    /// [`Style::Plain`] returns the keys as written. Keys that fail to parse
+    /// are also returned as written.
+    pub fn styled_keys(&self, style: Style) -> Vec<String> {
+        self.keys.iter().map(|key| styled_key(key, style)).collect()
+    }
+
+    /// Returns the symbol formatted in `style`.
+    ///
+    /// A custom symbol (e.g. `"␣"`) is returned as is, while a symbol filled
+    /// in from the first key follows the style like [`Item::styled_keys`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use keymap::{Item, style::Style};
+    /// let item = Item::new(vec!["ctrl-s".into()], "Save".into());
+    /// assert_eq!(item.styled_symbol(Style::Windows).as_deref(), Some("Ctrl+S"));
+    ///
+    /// let item = item.with_symbol(Some("💾"));
+    /// assert_eq!(item.styled_symbol(Style::Windows).as_deref(), Some("💾"));
+    /// ```
+    pub fn styled_symbol(&self, style: Style) -> Option<String> {
+        let symbol = self.symbol.as_ref()?;
+        match self.keys.first() {
+            Some(first) if first == symbol => Some(styled_key(first, style)),
+            _ => Some(symbol.clone()),
+        }
+    }
+}
+
+fn styled_key(key: &str, style: Style) -> String {
+    match parse_seq(key) {
+        Ok(nodes) if style != Style::Plain => nodes.styled(style).to_string(),
+        _ => key.to_string(),
+    }
+}
+
+/// Custom serialization for [`Item`], writing the same fields that
+/// `ItemRaw` reads.
+///
+/// Fields holding their default value are skipped, i.e. an empty
+/// `description`, a missing `help`, `when`, `group` or `order`, and a
+/// `symbol` equal to the first key (which is the fallback applied when
+/// reading it back).
+impl Serialize for Item {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let symbol = self
+            .symbol
+            .as_ref()
+            .filter(|symbol| Some(*symbol) != self.keys.first());
+
+        let mut state = serializer.serialize_struct("Item", 7)?;
+        state.serialize_field("keys", &self.keys)?;
+
+        if self.description.is_empty() {
+            state.skip_field("description")?;
+        } else {
+            state.serialize_field("description", &self.description)?;
+        }
+
+        match symbol {
+            Some(symbol) => state.serialize_field("symbol", symbol)?,
+            None => state.skip_field("symbol")?,
+        }
+
+        match &self.help {
+            Some(help) => state.serialize_field("help", help)?,
+            None => state.skip_field("help")?,
+        }
+
+        match &self.when {
+            Some(when) => state.serialize_field("when", when)?,
+            None => state.skip_field("when")?,
+        }
+
+        match &self.group {
+            Some(group) => state.serialize_field("group", group)?,
+            None => state.skip_field("group")?,
+        }
+
+        match &self.order {
+            Some(order) => state.serialize_field("order", order)?,
+            None => state.skip_field("order")?,
+        }
+
+        state.end()
+    }
+}
+
+/// Custom serialization for [`Config<T>`], writing a map of `T = Item`
+/// entries in insertion order. This is the same format read by its
+/// [`Deserialize`] implementation.
+///
+/// ```toml
+/// Create = { keys = ["c"], description = "Create a new item" }
+/// Delete = { keys = ["d", "d e"], description = "Delete an item" }
+/// ```
+impl<T: Serialize> Serialize for Config<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(self.items.len()))?;
+        for (t, item) in &self.items {
+            map.serialize_entry(t, item)?;
+        }
+
+        map.end()
+    }
+}
+
+/// Serializes all items of a [`DerivedConfig<T>`], including defaults, in
+/// the same format as [`Config<T>`].
+impl<T: Serialize> Serialize for DerivedConfig<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.0.serialize(serializer)
+    }
 }
 
 /// Custom deserialization logic for [`Config<T>`], enabling a configuration format map of `T = Item` entries. During deserialization,
@@ -557,7 +1736,7 @@ where
                     items.push((t, item));
                 }
 
-                Ok(Config::new(items))
+                Config::try_new(items).map_err(de::Error::custom)
             }
         }
 
@@ -575,6 +1754,27 @@ where
 ///    included unless overridden by the user.
 /// 2. If the user’s config contains a key type `T` that matches a default,
 ///    the `Item` is replaced. Otherwise, a new `(T, Item)` is appended.
+/// 3. Default bindings can be removed, either entirely or key by key:
+///
+/// ```toml
+/// # Disable the action (all of its keys are unbound)
+/// Quit = false
+/// # Same as above, but keeps a custom description
+/// Delete = { keys = [], description = "Disabled" }
+/// # Strip specific default keys, keeping the rest
+/// Create = { unbind = ["c"] }
+/// # Reset fields instead of keeping their defaults
+/// Update = { clear = ["help", "group"] }
+/// ```
+///
+/// 4. Default keys can be extended instead of replaced:
+///
+/// ```toml
+/// # Keeps the default keys of `Left` (e.g. "left") and adds "h"
+/// Left = { add_keys = ["h"] }
+/// # `remove_keys` is an alias of `unbind`
+/// Right = { add_keys = ["l"], remove_keys = ["right"] }
+/// ```
 ///
 /// # Trait Bounds
 ///
@@ -609,31 +1809,14 @@ where
                 // Start with the default items from KeyMapConfig
                 let mut config = T::keymap_config();
 
-                // Merge user-specified entries: replace or append
-                while let Some((t, mut item)) = map.next_entry::<T, Item>()? {
-                    if let Some(pos) = config
-                        .items
-                        .iter()
-                        .position(|(existing_key, _)| existing_key == &t)
-                    {
-                        // Override the default Item if the key matches
-                        if item.description.is_empty() {
-                            item.description = config.items[pos].1.description.clone();
-                        }
-                        if item.help.is_none() {
-                            item.help = config.items[pos].1.help.clone();
-                        }
-                        if item.symbol.is_none() && item.keys == config.items[pos].1.keys {
-                            item.symbol = config.items[pos].1.symbol.clone();
-                        }
-                        config.items[pos].1 = item;
-                    } else {
-                        // Append a new entry
-                        config.items.push((t, item));
-                    }
+                // Merge user-specified entries: replace, disable or append
+                while let Some((t, entry)) = map.next_entry::<T, EntryRaw>()? {
+                    merge_entry(&mut config.items, t, entry).map_err(de::Error::custom)?;
                 }
 
-                Ok(DerivedConfig(Config::new(config.items)))
+                Config::try_new(config.items)
+                    .map(DerivedConfig)
+                    .map_err(de::Error::custom)
             }
         }
 
@@ -650,7 +1833,7 @@ mod tests {
     "#;
 
     // #[derive(KeyMap)]
-    #[derive(Debug, Deserialize, PartialEq, Eq, Hash)]
+    #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, Hash)]
     enum Action {
         // #[key("n")]
         Create,
@@ -759,4 +1942,553 @@ mod tests {
         assert_eq!(*action, Action::Create);
         assert_eq!(item.description, "Default Create");
     }
+
+    #[test]
+    fn test_try_new_collects_invalid_keys() {
+        let err = Config::try_new(vec![
+            (Action::Create, Item::new(vec!["c".into()], "".into())),
+            (
+                Action::Delete,
+                Item::new(vec!["enter2".into(), "d".into(), "@foo".into()], "".into()),
+            ),
+        ])
+        .unwrap_err();
+
+        let keys = err
+            .invalid_keys
+            .iter()
+            .map(|invalid| (invalid.index, invalid.key.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(keys, vec![(1, "enter2"), (1, "@foo")]);
+        assert_eq!(err.invalid_keys[0].error, parse_seq("enter2").unwrap_err());
+    }
+
+    #[test]
+    fn test_deserialize_invalid_keys() {
+        let toml = r#"
+            Create = { keys = ["c", "ctrl-"] }
+        "#;
+
+        let err = toml::from_str::<Config<Action>>(toml).unwrap_err();
+        assert!(err.message().contains(r#"invalid key "ctrl-" (item 0)"#));
+
+        let err = toml::from_str::<DerivedConfig<Action>>(toml).unwrap_err();
+        assert!(err.message().contains(r#"invalid key "ctrl-" (item 0)"#));
+    }
+
+    #[test]
+    fn test_serialize_round_trip() {
+        let config: Config<String> = toml::from_str(
+            r#"
+            Create = { keys = ["c"], description = "Create a new item", help = "create" }
+            Delete = { keys = ["d", "d e", "@digit"], symbol = "D" }
+            Quit = { keys = ["shift-cmd-del"] }
+        "#,
+        )
+        .unwrap();
+
+        let output = toml::to_string(&config).unwrap();
+        assert_eq!(
+            output,
+            r#"[Create]
+keys = ["c"]
+description = "Create a new item"
+help = "create"
+
+[Delete]
+keys = ["d", "d e", "@digit"]
+symbol = "D"
+
+[Quit]
+keys = ["shift-cmd-del"]
+"#
+        );
+
+        let reloaded: Config<String> = toml::from_str(&output).unwrap();
+        assert_eq!(reloaded.items, config.items);
+    }
+
+    #[test]
+    fn test_derived_config_overrides() {
+        let config: DerivedConfig<Action> = toml::from_str(
+            r#"
+            Create = { keys = ["c"], description = "Default Create" }
+            Update = { keys = ["x"] }
+            Delete = { keys = ["d"], description = "Custom delete", help = "del" }
+        "#,
+        )
+        .unwrap();
+
+        let overrides = config.overrides();
+        assert_eq!(
+            toml::to_string(&overrides).unwrap(),
+            r#"[Update]
+keys = ["x"]
+
+[Delete]
+description = "Custom delete"
+help = "del"
+"#
+        );
+
+        let reloaded: DerivedConfig<Action> =
+            toml::from_str(&toml::to_string(&overrides).unwrap()).unwrap();
+        assert_eq!(reloaded.items, config.items);
+
+        // Symbols filled in from the keys are left out, custom ones are kept
+        let config: DerivedConfig<Action> = toml::from_str(
+            r#"
+            Create = { add_keys = ["n"] }
+            Update = { keys = ["x"] }
+            Delete = { keys = ["x"], symbol = "D" }
+        "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            toml::to_string(&config.overrides()).unwrap(),
+            r#"[Create]
+keys = ["c", "n"]
+
+[Update]
+keys = ["x"]
+
+[Delete]
+keys = ["x"]
+symbol = "D"
+"#
+        );
+    }
+
+    #[test]
+    fn test_derived_config_overrides_keep_default_keys() {
+        let config: DerivedConfig<Action> =
+            toml::from_str(r#"Create = { description = "New file" }"#).unwrap();
+
+        // Unchanged keys are left out, so the defaults of later releases apply
+        let data = toml::to_string(&config.overrides()).unwrap();
+        assert_eq!(data, "[Create]\ndescription = \"New file\"\n");
+
+        let reloaded: DerivedConfig<Action> = toml::from_str(&data).unwrap();
+        assert_eq!(reloaded.items, config.items);
+        assert_eq!(reloaded.items[0].1.keys, vec!["c"]);
+    }
+
+    #[test]
+    fn test_derived_config_clear() {
+        let config: DerivedConfig<Action> = toml::from_str(
+            r#"
+            Create = { clear = ["description", "symbol"] }
+            Update = { keys = ["x"], clear = ["symbol"] }
+        "#,
+        )
+        .unwrap();
+
+        let (_, item) = &config.items[0];
+        assert_eq!(item.keys, vec!["c"]);
+        assert_eq!(item.description, "");
+        assert_eq!(item.symbol, None);
+        assert_eq!(config.items[1].1.symbol, None);
+
+        // Fields removed from the defaults are written as `clear`
+        let data = toml::to_string(&config.overrides()).unwrap();
+        assert_eq!(
+            data,
+            r#"[Create]
+clear = ["description", "symbol"]
+
+[Update]
+keys = ["x"]
+clear = ["symbol"]
+"#
+        );
+
+        let reloaded: DerivedConfig<Action> = toml::from_str(&data).unwrap();
+        assert_eq!(reloaded.items, config.items);
+    }
+
+    #[test]
+    fn test_derived_config_unbind() {
+        let config: DerivedConfig<Action> = toml::from_str(
+            r#"
+            Create = false
+            Update = { keys = [] }
+            Delete = { unbind = ["d"] }
+        "#,
+        )
+        .unwrap();
+
+        assert!(config.get_item_by_key_str("c").is_none());
+        assert!(config.get_item_by_key_str("u").is_none());
+        assert!(config.get_item_by_key_str("d").is_none());
+
+        // Disabled items are kept (e.g. for help screens) without keys
+        let (_, item) = &config.items[0];
+        assert_eq!(item.keys, Vec::<String>::new());
+        assert_eq!(item.description, "Default Create");
+
+        // Unbound keys are compared by their parsed form
+        let config: DerivedConfig<Action> = toml::from_str(
+            r#"
+            Delete = { keys = ["d", "delete", "x"], unbind = ["del"] }
+            Update = true
+        "#,
+        )
+        .unwrap();
+
+        let (_, item) = &config.items[2];
+        assert_eq!(item.keys, vec!["d", "x"]);
+        assert!(config.get_item_by_key_str("u").is_some());
+
+        // Entries without defaults still require keys
+        let err = toml::from_str::<Config<Action>>("Create = { unbind = [\"c\"] }").unwrap_err();
+        assert!(err.message().contains("missing field `keys`"));
+
+        // Disabled items round trip through overrides
+        let config: DerivedConfig<Action> = toml::from_str("Create = false").unwrap();
+        let reloaded: DerivedConfig<Action> =
+            toml::from_str(&toml::to_string(&config.overrides()).unwrap()).unwrap();
+        assert_eq!(reloaded.items, config.items);
+    }
+
+    #[test]
+    fn test_derived_config_add_keys() {
+        let config: DerivedConfig<Action> = toml::from_str(
+            r#"
+            Create = { add_keys = ["n", "c"] }
+            Update = { add_keys = ["x"], remove_keys = ["u"] }
+            Delete = { keys = ["x"], add_keys = ["d d"] }
+        "#,
+        )
+        .unwrap();
+
+        let keys = config
+            .items
+            .iter()
+            .map(|(_, item)| item.keys.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(keys, vec![vec!["c", "n"], vec!["x"], vec!["x", "d d"]]);
+
+        // The default description and symbol are kept while the first key is unchanged
+        let (action, item) = config.get_item_by_key_str("n").unwrap();
+        assert_eq!(*action, Action::Create);
+        assert_eq!(item.description, "Default Create");
+        assert_eq!(item.symbol.as_deref(), Some("c"));
+
+        let reloaded: DerivedConfig<Action> =
+            toml::from_str(&toml::to_string(&config.overrides()).unwrap()).unwrap();
+        assert_eq!(reloaded.items, config.items);
+    }
+
+    #[test]
+    fn test_get_with_context() {
+        let config: Config<String> = toml::from_str(
+            r#"
+            Newline = { keys = ["enter"] }
+            Submit = { keys = ["enter"], when = "inputFocus && !multiline" }
+            Suggest = { keys = ["enter"], when = "suggestVisible" }
+        "#,
+        )
+        .unwrap();
+        let enter = parse_seq("enter").unwrap();
+        let get = |context: &[&str]| {
+            config
+                .get_item_by_keymaps_with(&enter, context)
+                .map(|(t, _)| t.as_str())
+        };
+
+        assert_eq!(get(&[]), Some("Newline"));
+        assert_eq!(get(&["inputFocus"]), Some("Submit"));
+        assert_eq!(get(&["inputFocus", "multiline"]), Some("Newline"));
+        assert_eq!(get(&["inputFocus", "suggestVisible"]), Some("Suggest"));
+
+        // Context-free lookups skip `when`, so the unconditional binding wins
+        assert_eq!(config.get_item_by_keymaps(&enter).unwrap().0, "Newline");
+
+        // `Suggest` shadows `Submit` when both clauses hold
+        let diagnostics = config.analyze();
+        assert_eq!(diagnostics.len(), 3);
+        assert!(matches!(
+            diagnostics[2],
+            Diagnostic::DuplicatePattern { first, second }
+                if first.value == "Submit" && second.value == "Suggest"
+        ));
+        assert_eq!(
+            diagnostics[2].when().unwrap().to_string(),
+            "inputFocus && !multiline && suggestVisible"
+        );
+
+        let err =
+            toml::from_str::<Config<String>>(r#"Submit = { keys = ["enter"], when = "a &&" }"#)
+                .unwrap_err();
+        assert!(err.to_string().contains("expected a context key"), "{err}");
+    }
+
+    #[test]
+    fn test_when_fallback() {
+        let config: Config<String> = toml::from_str(
+            r#"
+            Insert = { keys = ["@any"] }
+            Submit = { keys = ["enter"], when = "inputFocus" }
+            Save = { keys = ["ctrl-x ctrl-s"], when = "editorFocus" }
+        "#,
+        )
+        .unwrap();
+        let enter = parse_seq("enter").unwrap();
+        let save = parse_seq("ctrl-x ctrl-s").unwrap();
+
+        // Without a context, gated bindings fall back to the key group
+        assert_eq!(config.get_item_by_keymaps(&enter).unwrap().0, "Insert");
+        assert_eq!(
+            config
+                .get_item_by_keymaps_with(&enter, &["other"][..])
+                .unwrap()
+                .0,
+            "Insert"
+        );
+        assert_eq!(
+            config
+                .get_item_by_keymaps_with(&enter, &["inputFocus"][..])
+                .unwrap()
+                .0,
+            "Submit"
+        );
+
+        // The resolver skips gated bindings like the lookups do
+        let mut resolver = Resolver::new();
+        assert!(matches!(
+            &config.resolve_keymap(&mut resolver, enter[0].clone())[..],
+            [Resolution::Matched(action, _)] if *action == "Insert"
+        ));
+        assert!(matches!(
+            &config.resolve_keymap_with(&mut resolver, enter[0].clone(), &["inputFocus"][..])[..],
+            [Resolution::Matched(action, _)] if *action == "Submit"
+        ));
+
+        // Gated-only sequences are not prefixes without their context
+        assert!(matches!(
+            &config.resolve_keymap(&mut resolver, save[0].clone())[..],
+            [Resolution::Matched(action, _)] if *action == "Insert"
+        ));
+        assert!(!resolver.is_pending());
+
+        assert_eq!(
+            config
+                .get_item_by_keymaps_with(&save, &["editorFocus"][..])
+                .unwrap()
+                .0,
+            "Save"
+        );
+        let resolutions = save
+            .iter()
+            .flat_map(|node| {
+                config.resolve_keymap_with(&mut resolver, node.clone(), &["editorFocus"][..])
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            resolutions,
+            vec![
+                Resolution::Pending,
+                Resolution::Matched(&"Save".to_string(), save.clone())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_derived_config_when() {
+        let config: DerivedConfig<Action> = toml::from_str(
+            r#"
+            Create = { keys = ["c"], when = "editorFocus" }
+            Update = { when = "!readOnly" }
+        "#,
+        )
+        .unwrap();
+
+        let c = parse_seq("c").unwrap();
+        assert_eq!(config.get_item_by_keymaps_with(&c, &["other"][..]), None);
+        assert_eq!(
+            config
+                .get_item_by_keymaps_with(&c, &["editorFocus"][..])
+                .unwrap()
+                .0,
+            &Action::Create
+        );
+
+        // The default keys are kept and the clause survives a round trip
+        let data = toml::to_string(&config.overrides()).unwrap();
+        assert!(data.contains(r#"when = "!readOnly""#), "{data}");
+        let reloaded: DerivedConfig<Action> = toml::from_str(&data).unwrap();
+        assert_eq!(reloaded.items, config.items);
+    }
+
+    #[test]
+    fn test_keys_for() {
+        let config: DerivedConfig<Action> = toml::from_str(
+            r#"
+            Create = { keys = ["ctrl-n", "del", "c c"] }
+            Delete = { keys = ["delete", "d"] }
+        "#,
+        )
+        .unwrap();
+
+        let keys = config.keys_for(&Action::Create);
+        let display = keys.iter().map(ToString::to_string).collect::<Vec<_>>();
+        // `del` is taken over by Delete's `delete`
+        assert_eq!(display, vec!["ctrl-n", "c c"]);
+        assert_eq!(keys[0].key, "ctrl-n");
+        assert_eq!(keys[1].nodes, parse_seq("c c").unwrap());
+
+        assert_eq!(
+            config.primary_key_for(&Action::Delete).unwrap().to_string(),
+            "delete"
+        );
+        assert_eq!(
+            config.primary_key_for(&Action::Update).unwrap().to_string(),
+            "u"
+        );
+    }
+
+    #[test]
+    fn test_grouped_items() {
+        let config: DerivedConfig<Action> = toml::from_str(
+            r#"
+            Create = { group = "Editing", order = 2 }
+            Delete = { category = "Editing", order = 1 }
+        "#,
+        )
+        .unwrap();
+
+        let groups = config
+            .grouped_items()
+            .into_iter()
+            .map(|(group, items)| (group, items.into_iter().map(|(t, _)| t.clone()).collect()))
+            .collect::<Vec<(_, Vec<_>)>>();
+        assert_eq!(
+            groups,
+            vec![
+                (None, vec![Action::Update]),
+                (Some("Editing"), vec![Action::Delete, Action::Create]),
+            ]
+        );
+
+        let data = toml::to_string(&config.overrides()).unwrap();
+        let reloaded: DerivedConfig<Action> = toml::from_str(&data).unwrap();
+        assert_eq!(reloaded.items, config.items);
+    }
+
+    #[test]
+    fn test_resolve_sequences() {
+        let config: Config<Action> = toml::from_str(CONFIG).unwrap();
+        let mut resolver = Resolver::new();
+        let mut feed =
+            |key: &str| config.resolve_keymap(&mut resolver, parse_seq(key).unwrap()[0].clone());
+
+        // "d" is bound on its own and as a prefix of "d e"
+        assert_eq!(feed("d"), vec![Resolution::Pending]);
+        assert_eq!(
+            feed("e"),
+            vec![Resolution::Matched(
+                &Action::Delete,
+                parse_seq("d e").unwrap()
+            )]
+        );
+
+        // "c" breaks the pending "d", so "d" fires before "c" is resolved
+        assert_eq!(feed("d"), vec![Resolution::Pending]);
+        assert_eq!(
+            feed("c"),
+            vec![
+                Resolution::Matched(&Action::Delete, parse_seq("d").unwrap()),
+                Resolution::Matched(&Action::Create, parse_seq("c").unwrap()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_continuations() {
+        let config: Config<Action> = toml::from_str(CONFIG).unwrap();
+
+        let next = config.continuations(&parse_seq("d").unwrap());
+        assert_eq!(next.len(), 1);
+        assert_eq!(next[0].node, parse_seq("e").unwrap()[0]);
+        assert!(!next[0].is_prefix);
+
+        let (action, item) = next[0].value.unwrap();
+        assert_eq!(*action, Action::Delete);
+        assert_eq!(item.description, "Delete an item");
+
+        // "d" is both bound on its own and the start of "d e"
+        let first = config.continuations(&[]);
+        let d = first
+            .iter()
+            .find(|next| next.node == parse_seq("d").unwrap()[0])
+            .unwrap();
+        assert!(d.is_prefix);
+        assert_eq!(d.value.map(|(action, _)| action), Some(&Action::Delete));
+    }
+
+    #[test]
+    fn test_continuations_when() {
+        let config: Config<String> = toml::from_str(
+            r#"
+            Next = { keys = ["ctrl-b n"] }
+            Hidden = { keys = ["ctrl-b h"], when = "never" }
+            Deep = { keys = ["ctrl-b x y"], when = "never" }
+            Split = { keys = ["ctrl-b s"] }
+            SplitRight = { keys = ["ctrl-b s l"], when = "never" }
+        "#,
+        )
+        .unwrap();
+        let prefix = parse_seq("ctrl-b").unwrap();
+        let list = |next: Vec<Continuation<(&String, &Item)>>| {
+            next.into_iter()
+                .map(|c| {
+                    (
+                        c.node.to_string(),
+                        c.value.map(|(t, _)| t.to_string()),
+                        c.is_prefix,
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        let row = |node: &str, value: Option<&str>, is_prefix| {
+            (node.to_string(), value.map(str::to_string), is_prefix)
+        };
+
+        // Keys bound only by gated items are neither listed nor prefixes
+        assert_eq!(
+            list(config.continuations(&prefix)),
+            vec![
+                row("n", Some("Next"), false),
+                row("s", Some("Split"), false),
+            ]
+        );
+        assert_eq!(
+            list(config.continuations_with(&prefix, &["never"][..])),
+            vec![
+                row("h", Some("Hidden"), false),
+                row("n", Some("Next"), false),
+                row("s", Some("Split"), true),
+                row("x", None, true),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolve_timeout() {
+        use std::time::Duration;
+
+        let start = Instant::now();
+        let config: Config<Action> = toml::from_str(CONFIG).unwrap();
+        let mut resolver = Resolver::with_clock(move || start).with_timeout(Duration::from_secs(1));
+
+        let d = parse_seq("d").unwrap();
+        config.resolve_keymap(&mut resolver, d[0].clone());
+
+        assert_eq!(config.poll(&mut resolver, start), vec![]);
+        assert_eq!(
+            config.poll(&mut resolver, start + Duration::from_secs(1)),
+            vec![Resolution::Matched(&Action::Delete, d)]
+        );
+    }
 }
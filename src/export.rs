@@ -0,0 +1,898 @@
+//! # Exporters
+//!
+//! Exporters write the bindings of a [`Config<T>`] in the notation of other
+//! applications, the reverse of [`import`](crate::import):
+//!
+//! | Target              | Output                              | `ctrl-a`   |
+//! | ------------------- | ----------------------------------- | ---------- |
+//! | [`Target::VsCode`]  | `keybindings.json` entries          | `ctrl+a`   |
+//! | [`Target::Helix`]   | A `[keys.<mode>]` table             | `C-a`      |
+//! | [`Target::Tmux`]    | `bind-key -T <table>` commands      | `C-a`      |
+//! | [`Target::Vim`]     | `<mode>noremap` commands            | `<C-a>`    |
+//!
+//! Each key of an [`Item`] is exported as a separate binding of the item's
+//! command. Items without keys are skipped. Command separators are escaped
+//! (`\;` for tmux and `<Bar>` for vim) so the whole command stays bound.
+//!
+//! Constructs that have no equivalent in the target, such as key groups
+//! (`@digit`), key states other than `press` (`release-space`) or `when`
+//! clauses outside of VS Code, return an [`ExportError`] instead of being
+//! dropped.
+use std::fmt::{self, Display, Write};
+
+use keymap_parser::{
+    node::{Key, Modifier, State},
+    parse_seq,
+    style::{Style, StyledDisplay},
+    Node,
+};
+
+use crate::{Config, Item};
+
+/// The application to export to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target<'a> {
+    /// A VS Code `keybindings.json` array.
+    VsCode,
+    /// A Helix `config.toml` key table for a mode (e.g. `"normal"`).
+    Helix(&'a str),
+    /// tmux `bind-key` commands for a key table (e.g. `"prefix"` or `"root"`).
+    Tmux(&'a str),
+    /// vim mapping commands for a mode (e.g. `"normal"` or `"insert"`), named
+    /// as in [`import::vim`](crate::import::vim).
+    Vim(&'a str),
+}
+
+/// An error returned when a binding cannot be exported.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExportError {
+    /// The key has no equivalent in the target (e.g. `@digit`), or is not a
+    /// valid key sequence.
+    UnsupportedKey { key: String, command: String },
+    /// The target has no conditional bindings.
+    UnsupportedWhen { key: String, command: String },
+    /// The target only binds single keys (tmux).
+    UnsupportedSequence { key: String, command: String },
+    /// The key is bound and also starts a longer sequence (Helix).
+    PrefixConflict { key: String, command: String },
+    /// The vim mode does not exist.
+    UnknownMode(String),
+}
+
+impl Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportError::UnsupportedKey { key, command } => {
+                write!(f, "key {key:?} of {command:?} has no equivalent")
+            }
+            ExportError::UnsupportedWhen { key, command } => {
+                write!(
+                    f,
+                    "key {key:?} of {command:?} has an unsupported when clause"
+                )
+            }
+            ExportError::UnsupportedSequence { key, command } => {
+                write!(
+                    f,
+                    "key {key:?} of {command:?} is an unsupported key sequence"
+                )
+            }
+            ExportError::PrefixConflict { key, command } => {
+                write!(
+                    f,
+                    "key {key:?} of {command:?} conflicts with a longer sequence"
+                )
+            }
+            ExportError::UnknownMode(mode) => write!(f, "unknown mode {mode:?}"),
+        }
+    }
+}
+
+impl std::error::Error for ExportError {}
+
+impl<T: Display> Config<T> {
+    /// Exports the bindings to `target`, using the `Display` form of `T` as
+    /// the command.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`ExportError`] for the first binding the target cannot
+    /// express.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use keymap::{Config, export::Target};
+    /// let config: Config<String> = toml::from_str(r#"
+    ///     ":Files<CR>" = { keys = ["space f f"] }
+    /// "#).unwrap();
+    ///
+    /// let vim = config.export(Target::Vim("normal")).unwrap();
+    /// assert_eq!(vim, "nnoremap <Space>ff :Files<CR>\n");
+    /// ```
+    pub fn export(&self, target: Target) -> Result<String, ExportError> {
+        self.export_with(target, |t| t.to_string())
+    }
+}
+
+impl<T> Config<T> {
+    /// Exports the bindings to `target`, using `command` to name the command
+    /// of each `T`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`ExportError`] for the first binding the target cannot
+    /// express.
+    pub fn export_with<F>(&self, target: Target, command: F) -> Result<String, ExportError>
+    where
+        F: Fn(&T) -> String,
+    {
+        let bindings = self
+            .items
+            .iter()
+            .flat_map(|(t, item)| {
+                let command = command(t);
+                item.keys.iter().map(move |key| {
+                    // `items` is public, so keys may not have been validated
+                    let nodes = parse_seq(key)
+                        .ok()
+                        .filter(|nodes| !nodes.is_empty())
+                        .ok_or_else(|| ExportError::UnsupportedKey {
+                            key: key.to_string(),
+                            command: command.clone(),
+                        })?;
+
+                    Ok(Binding {
+                        key,
+                        nodes,
+                        command: command.clone(),
+                        item,
+                    })
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        match target {
+            Target::VsCode => vscode(&bindings),
+            Target::Helix(mode) => helix(&bindings, mode),
+            Target::Tmux(table) => tmux(&bindings, table),
+            Target::Vim(mode) => vim(&bindings, mode),
+        }
+    }
+}
+
+/// A single key of an item.
+struct Binding<'a> {
+    key: &'a str,
+    nodes: Vec<Node>,
+    command: String,
+    item: &'a Item,
+}
+
+impl Binding<'_> {
+    fn error(&self, f: fn(String, String) -> ExportError) -> ExportError {
+        f(self.key.to_string(), self.command.clone())
+    }
+
+    fn unsupported_key(&self) -> ExportError {
+        self.error(|key, command| ExportError::UnsupportedKey { key, command })
+    }
+
+    /// Fails for bindings with a when clause.
+    fn unconditional(&self) -> Result<(), ExportError> {
+        match self.item.when {
+            Some(_) => {
+                Err(self.error(|key, command| ExportError::UnsupportedWhen { key, command }))
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Formats each node with `f`, failing if any has no equivalent.
+    fn nodes<F>(&self, f: F) -> Result<Vec<String>, ExportError>
+    where
+        F: Fn(&Node) -> Option<String>,
+    {
+        self.nodes.iter().map(|node| self.node(node, &f)).collect()
+    }
+
+    /// Formats a node with `f`, failing if it has no equivalent. The targets
+    /// only bind key presses, so releases, repeats and held keys have none.
+    fn node<F>(&self, node: &Node, f: F) -> Result<String, ExportError>
+    where
+        F: Fn(&Node) -> Option<String>,
+    {
+        match node.state {
+            None | Some(State::Pressed) => {
+                f(&node.clone().with_state(None)).ok_or_else(|| self.unsupported_key())
+            }
+            Some(_) => Err(self.unsupported_key()),
+        }
+    }
+}
+
+fn has(node: &Node, modifier: Modifier) -> bool {
+    node.modifiers & modifier as u8 != 0
+}
+
+/// Quotes a string for JSON or TOML, which share the basic escapes.
+fn quote(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for ch in s.chars() {
+        match ch {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            '\r' => quoted.push_str("\\r"),
+            ch if ch.is_control() => {
+                let _ = write!(quoted, "\\u{:04x}", ch as u32);
+            }
+            ch => quoted.push(ch),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+fn vscode(bindings: &[Binding]) -> Result<String, ExportError> {
+    let mut entries = Vec::new();
+
+    for binding in bindings {
+        let key = binding.nodes(vscode_node)?.join(" ");
+        let mut entry = format!(
+            "    {{\n        \"key\": {},\n        \"command\": {}",
+            quote(&key),
+            quote(&binding.command)
+        );
+        if let Some(when) = &binding.item.when {
+            let _ = write!(entry, ",\n        \"when\": {}", quote(&when.to_string()));
+        }
+        entry.push_str("\n    }");
+        entries.push(entry);
+    }
+
+    if entries.is_empty() {
+        return Ok("[]\n".to_string());
+    }
+    Ok(format!("[\n{}\n]\n", entries.join(",\n")))
+}
+
+fn vscode_node(node: &Node) -> Option<String> {
+    let mut shift = has(node, Modifier::Shift);
+    let key = match &node.key {
+        Key::Esc => "escape".to_string(),
+        Key::BackTab => {
+            shift = true;
+            "tab".to_string()
+        }
+        Key::Char(ch) if ch.is_ascii_uppercase() => {
+            shift = true;
+            ch.to_ascii_lowercase().to_string()
+        }
+        Key::Char(ch) => ch.to_string(),
+        Key::Pause => "pausebreak".to_string(),
+        Key::Menu => "contextmenu".to_string(),
+        Key::Keypad(n) => format!("numpad{n}"),
+        Key::KeypadPlus => "numpad_add".to_string(),
+        Key::KeypadMinus => "numpad_subtract".to_string(),
+        Key::KeypadMultiply => "numpad_multiply".to_string(),
+        Key::KeypadDivide => "numpad_divide".to_string(),
+        Key::KeypadDecimal => "numpad_decimal".to_string(),
+        Key::PlayPause => "mediaplaypause".to_string(),
+        Key::Stop => "mediastop".to_string(),
+        Key::NextTrack => "mediatracknext".to_string(),
+        Key::PrevTrack => "mediatrackprevious".to_string(),
+        Key::VolumeUp => "audiovolumeup".to_string(),
+        Key::VolumeDown => "audiovolumedown".to_string(),
+        Key::Mute => "audiovolumemute".to_string(),
+        Key::KeypadEnter
+        | Key::Play
+        | Key::MediaPause
+        | Key::FastForward
+        | Key::Rewind
+        | Key::Record
+        | Key::Group(_) => return None,
+        key => plain_name(key),
+    };
+
+    let mut parts = Vec::new();
+    if has(node, Modifier::Ctrl) {
+        parts.push("ctrl");
+    }
+    if shift {
+        parts.push("shift");
+    }
+    if has(node, Modifier::Alt) {
+        parts.push("alt");
+    }
+    if has(node, Modifier::Cmd) {
+        parts.push("cmd");
+    }
+    parts.push(&key);
+
+    Some(parts.join("+"))
+}
+
+/// The key name as written in key expressions (e.g. `pageup`, `f5`).
+fn plain_name(key: &Key) -> String {
+    Node::from(key.clone()).to_string()
+}
+
+/// A node of the Helix key trie: a command or a table of longer sequences.
+enum Trie {
+    Command(String),
+    Table(Vec<(String, Trie)>),
+}
+
+fn helix(bindings: &[Binding], mode: &str) -> Result<String, ExportError> {
+    let mut root = Vec::new();
+
+    for binding in bindings {
+        binding.unconditional()?;
+        let keys = binding.nodes(helix_node)?;
+        insert(&mut root, &keys, &binding.command).map_err(|_| {
+            binding.error(|key, command| ExportError::PrefixConflict { key, command })
+        })?;
+    }
+
+    let mut output = format!("[keys.{}]\n", toml_key(mode));
+    for (key, node) in &root {
+        let _ = writeln!(output, "{} = {}", toml_key(key), helix_value(node));
+    }
+    Ok(output)
+}
+
+/// Inserts the command at `keys`, failing if a key is both bound and a
+/// prefix of a longer sequence.
+fn insert(table: &mut Vec<(String, Trie)>, keys: &[String], command: &str) -> Result<(), ()> {
+    let Some((key, rest)) = keys.split_first() else {
+        return Err(());
+    };
+
+    let index = match table.iter().position(|(k, _)| k == key) {
+        Some(index) => index,
+        None => {
+            let node = match rest.is_empty() {
+                true => Trie::Command(String::new()),
+                false => Trie::Table(Vec::new()),
+            };
+            table.push((key.clone(), node));
+            table.len() - 1
+        }
+    };
+
+    match (&mut table[index].1, rest.is_empty()) {
+        // A later binding of the same keys wins, as in `Config`
+        (Trie::Command(existing), true) => {
+            *existing = command.to_string();
+            Ok(())
+        }
+        (Trie::Table(next), false) => insert(next, rest, command),
+        _ => Err(()),
+    }
+}
+
+fn helix_value(node: &Trie) -> String {
+    match node {
+        Trie::Command(command) => quote(command),
+        Trie::Table(table) => {
+            let entries = table
+                .iter()
+                .map(|(key, node)| format!("{} = {}", toml_key(key), helix_value(node)))
+                .collect::<Vec<_>>();
+            format!("{{ {} }}", entries.join(", "))
+        }
+    }
+}
+
+/// Quotes a TOML key unless it is a valid bare key.
+fn toml_key(key: &str) -> String {
+    if !key.is_empty()
+        && key
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '_' || ch == '-')
+    {
+        key.to_string()
+    } else {
+        quote(key)
+    }
+}
+
+fn helix_node(node: &Node) -> Option<String> {
+    // Helix drops shift from char keys and expects the shifted char instead
+    let shifted_char = matches!(node.key, Key::Char(_)) && has(node, Modifier::Shift);
+
+    let mut prefix = String::new();
+    for (modifier, name) in [
+        (Modifier::Ctrl, "C-"),
+        (Modifier::Alt, "A-"),
+        (Modifier::Shift, "S-"),
+        (Modifier::Cmd, "Meta-"),
+    ] {
+        if has(node, modifier) && !(shifted_char && modifier == Modifier::Shift) {
+            prefix.push_str(name);
+        }
+    }
+
+    let key = match &node.key {
+        Key::BackTab if !has(node, Modifier::Shift) => {
+            prefix.push_str("S-");
+            "tab".to_string()
+        }
+        Key::BackTab => return None,
+        Key::Enter => "ret".to_string(),
+        Key::Delete => "del".to_string(),
+        Key::Insert => "ins".to_string(),
+        Key::F(n) => format!("F{n}"),
+        Key::Char(ch) if shifted_char => match ch {
+            ch if ch.is_ascii_alphabetic() => ch.to_ascii_uppercase().to_string(),
+            _ => return None,
+        },
+        Key::Char('-') => "minus".to_string(),
+        Key::Char(ch) => ch.to_string(),
+        Key::MediaPause => "pausemedia".to_string(),
+        Key::NextTrack => "tracknext".to_string(),
+        Key::PrevTrack => "trackprevious".to_string(),
+        Key::VolumeUp => "raisevolume".to_string(),
+        Key::VolumeDown => "lowervolume".to_string(),
+        Key::Mute => "mutevolume".to_string(),
+        Key::Group(_) => return None,
+        key if key.is_keypad() => return None,
+        key => plain_name(key),
+    };
+
+    Some(prefix + &key)
+}
+
+fn tmux(bindings: &[Binding], table: &str) -> Result<String, ExportError> {
+    let mut output = String::new();
+
+    for binding in bindings {
+        binding.unconditional()?;
+        let [node] = binding.nodes.as_slice() else {
+            return Err(
+                binding.error(|key, command| ExportError::UnsupportedSequence { key, command })
+            );
+        };
+        let key = binding.node(node, tmux_node)?;
+
+        let command = tmux_command(&binding.command);
+
+        let _ = writeln!(output, "bind-key -T {table} {key} {command}");
+    }
+
+    Ok(output)
+}
+
+/// Escapes the `;` command separators of `command`, so that all of it is
+/// bound to the key instead of running when the line is read.
+fn tmux_command(command: &str) -> String {
+    let mut escaped = String::with_capacity(command.len());
+    let mut prev = None;
+    for ch in command.chars() {
+        // Keep separators that are already escaped, e.g. `a \; b`
+        if ch == ';' && prev != Some('\\') {
+            escaped.push('\\');
+        }
+        escaped.push(ch);
+        prev = Some(ch);
+    }
+    escaped
+}
+
+fn tmux_node(node: &Node) -> Option<String> {
+    if has(node, Modifier::Cmd) {
+        return None;
+    }
+
+    let mut prefix = String::new();
+    for (modifier, name) in [
+        (Modifier::Ctrl, "C-"),
+        (Modifier::Alt, "M-"),
+        (Modifier::Shift, "S-"),
+    ] {
+        if has(node, modifier) {
+            prefix.push_str(name);
+        }
+    }
+
+    let key = match &node.key {
+        Key::Backspace => "BSpace".to_string(),
+        Key::BackTab => "BTab".to_string(),
+        Key::Delete => "DC".to_string(),
+        Key::Down => "Down".to_string(),
+        Key::End => "End".to_string(),
+        Key::Enter => "Enter".to_string(),
+        Key::Esc => "Escape".to_string(),
+        Key::Home => "Home".to_string(),
+        Key::Insert => "IC".to_string(),
+        Key::Left => "Left".to_string(),
+        Key::PageDown => "NPage".to_string(),
+        Key::PageUp => "PPage".to_string(),
+        Key::Right => "Right".to_string(),
+        Key::Space => "Space".to_string(),
+        Key::Tab => "Tab".to_string(),
+        Key::Up => "Up".to_string(),
+        // tmux only names F1 - F12
+        Key::F(n @ 1..=12) => format!("F{n}"),
+        Key::F(_) => return None,
+        Key::Keypad(n) => format!("KP{n}"),
+        Key::KeypadEnter => "KPEnter".to_string(),
+        Key::KeypadPlus => "KP+".to_string(),
+        Key::KeypadMinus => "KP-".to_string(),
+        Key::KeypadMultiply => "KP*".to_string(),
+        Key::KeypadDivide => "KP/".to_string(),
+        Key::KeypadDecimal => "KP.".to_string(),
+        Key::CapsLock
+        | Key::ScrollLock
+        | Key::NumLock
+        | Key::PrintScreen
+        | Key::Pause
+        | Key::Menu
+        | Key::Play
+        | Key::MediaPause
+        | Key::PlayPause
+        | Key::Stop
+        | Key::FastForward
+        | Key::Rewind
+        | Key::NextTrack
+        | Key::PrevTrack
+        | Key::Record
+        | Key::VolumeUp
+        | Key::VolumeDown
+        | Key::Mute => return None,
+        Key::Char(ch) if ch.is_ascii_alphanumeric() => ch.to_string(),
+        // Quote characters that tmux would read as syntax
+        Key::Char('\'') => "\"'\"".to_string(),
+        Key::Char(ch) => format!("'{ch}'"),
+        Key::Group(_) => return None,
+    };
+
+    match key.strip_prefix('\'').or_else(|| key.strip_prefix('"')) {
+        // Keep the modifiers inside the quotes, e.g. 'C-;'
+        Some(_) if !prefix.is_empty() => {
+            let quote = &key[..1];
+            Some(format!("{quote}{prefix}{}", &key[1..]))
+        }
+        _ => Some(prefix + &key),
+    }
+}
+
+fn vim(bindings: &[Binding], mode: &str) -> Result<String, ExportError> {
+    let command = match mode {
+        "normal" => "nnoremap",
+        "visual" => "xnoremap",
+        "select" => "snoremap",
+        "operator" => "onoremap",
+        "insert" => "inoremap",
+        "command" => "cnoremap",
+        "terminal" => "tnoremap",
+        _ => return Err(ExportError::UnknownMode(mode.to_string())),
+    };
+
+    let mut output = String::new();
+    for binding in bindings {
+        binding.unconditional()?;
+        let lhs = binding.nodes(vim_node)?.concat();
+
+        // A `|` would end the mapping and run the rest as a command
+        let rhs = binding.command.replace('|', "<Bar>");
+
+        let _ = writeln!(output, "{command} {lhs} {rhs}");
+    }
+
+    Ok(output)
+}
+
+fn vim_node(node: &Node) -> Option<String> {
+    match node.key {
+        // Vim names the keypad keys, but no lock or media keys
+        Key::CapsLock
+        | Key::ScrollLock
+        | Key::NumLock
+        | Key::PrintScreen
+        | Key::Pause
+        | Key::Menu
+        | Key::Play
+        | Key::MediaPause
+        | Key::PlayPause
+        | Key::Stop
+        | Key::FastForward
+        | Key::Rewind
+        | Key::NextTrack
+        | Key::PrevTrack
+        | Key::Record
+        | Key::VolumeUp
+        | Key::VolumeDown
+        | Key::Mute
+        | Key::Group(_) => None,
+        _ => Some(node.styled(Style::Vim).to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> Config<String> {
+        Config::new(vec![
+            (
+                "comment".to_string(),
+                Item::new(vec!["ctrl-k ctrl-c".into(), "ctrl-/".into()], String::new())
+                    .with_when(crate::when::parse("editorTextFocus && !readOnly").ok()),
+            ),
+            (
+                "save".to_string(),
+                Item::new(vec!["ctrl-s".into(), "cmd-shift-S".into()], String::new()),
+            ),
+            (
+                "goto_file_start".to_string(),
+                Item::new(
+                    vec!["g g".into(), "g -".into(), "esc".into()],
+                    String::new(),
+                ),
+            ),
+            ("disabled".to_string(), Item::new(vec![], String::new())),
+        ])
+    }
+
+    /// The config without when clauses.
+    fn unconditional() -> Config<String> {
+        let items = config()
+            .items
+            .into_iter()
+            .map(|(t, item)| (t, item.with_when(None)))
+            .collect();
+        Config::new(items)
+    }
+
+    #[test]
+    fn test_export_vscode() {
+        let json = config().export(Target::VsCode).unwrap();
+        assert!(json.starts_with(
+            r#"[
+    {
+        "key": "ctrl+k ctrl+c",
+        "command": "comment",
+        "when": "editorTextFocus && !readOnly"
+    },"#
+        ));
+        assert!(json.contains(r#""key": "shift+cmd+s","#));
+        assert!(json.contains(r#""key": "escape","#));
+        assert!(json.ends_with("}\n]\n"));
+    }
+
+    #[test]
+    fn test_export_helix() {
+        let toml = unconditional().export(Target::Helix("normal")).unwrap();
+        assert_eq!(
+            toml,
+            r#"[keys.normal]
+C-k = { C-c = "comment" }
+"C-/" = "comment"
+C-s = "save"
+Meta-S = "save"
+g = { g = "goto_file_start", minus = "goto_file_start" }
+esc = "goto_file_start"
+"#
+        );
+
+        let config: Config<String> = toml::from_str(
+            r#"
+            "goto" = { keys = ["g"] }
+            "goto_file_start" = { keys = ["g g"] }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.export(Target::Helix("normal")),
+            Err(ExportError::PrefixConflict {
+                key: "g g".to_string(),
+                command: "goto_file_start".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_export_tmux() {
+        let config: Config<String> = toml::from_str(
+            r#"
+            "send-prefix" = { keys = ["ctrl-a"] }
+            "split-window -h" = { keys = ["|", "alt-;"] }
+            "kill-pane" = { keys = ["x", "delete"] }
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.export(Target::Tmux("prefix")).unwrap(),
+            "\
+bind-key -T prefix x kill-pane
+bind-key -T prefix DC kill-pane
+bind-key -T prefix C-a send-prefix
+bind-key -T prefix '|' split-window -h
+bind-key -T prefix 'M-;' split-window -h
+"
+        );
+
+        // Command separators stay in the bound command
+        let config: Config<String> = toml::from_str(
+            r#"
+            "split-window ; select-pane -L" = { keys = ["s"] }
+            'new-window \; rename-window logs' = { keys = ["l"] }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.export(Target::Tmux("prefix")).unwrap(),
+            "\
+bind-key -T prefix l new-window \\; rename-window logs
+bind-key -T prefix s split-window \\; select-pane -L
+"
+        );
+
+        assert_eq!(
+            unconditional().export(Target::Tmux("prefix")),
+            Err(ExportError::UnsupportedSequence {
+                key: "ctrl-k ctrl-c".to_string(),
+                command: "comment".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_export_special_keys() {
+        type NodeFn = fn(&Node) -> Option<String>;
+
+        [
+            (vscode_node as NodeFn, "ctrl-kp5", Some("ctrl+numpad5")),
+            (vscode_node, "pause", Some("pausebreak")),
+            (vscode_node, "mute", Some("audiovolumemute")),
+            (vscode_node, "kpenter", None),
+            (helix_node, "volumeup", Some("raisevolume")),
+            (helix_node, "capslock", Some("capslock")),
+            (helix_node, "kp5", None),
+            (tmux_node, "kpenter", Some("KPEnter")),
+            (tmux_node, "ctrl-kpdivide", Some("C-KP/")),
+            (tmux_node, "capslock", None),
+            (vim_node, "ctrl-kpplus", Some("<C-kPlus>")),
+            (vim_node, "playpause", None),
+        ]
+        .into_iter()
+        .for_each(|(export, key, expected)| {
+            let node = crate::parser::parse(key).unwrap();
+            assert_eq!(export(&node).as_deref(), expected, "{key}");
+        });
+    }
+
+    #[test]
+    fn test_export_vim() {
+        assert_eq!(
+            unconditional().export(Target::Vim("normal")).unwrap(),
+            "\
+nnoremap <C-k><C-c> comment
+nnoremap <C-/> comment
+nnoremap <C-s> save
+nnoremap <S-D-S> save
+nnoremap gg goto_file_start
+nnoremap g- goto_file_start
+nnoremap <Esc> goto_file_start
+"
+        );
+        assert_eq!(
+            config().export(Target::Vim("replace")),
+            Err(ExportError::UnknownMode("replace".to_string()))
+        );
+
+        let config: Config<String> =
+            toml::from_str(r#"":ls | echo 'done'<CR>" = { keys = ["ctrl-l"] }"#).unwrap();
+        assert_eq!(
+            config.export(Target::Vim("normal")).unwrap(),
+            "nnoremap <C-l> :ls <Bar> echo 'done'<CR>\n"
+        );
+    }
+
+    #[test]
+    fn test_export_errors() {
+        let count = Config::new(vec![(
+            "count",
+            Item::new(vec!["@digit".into()], String::new()),
+        )]);
+        let err = count.export(Target::VsCode).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            r#"key "@digit" of "count" has no equivalent"#
+        );
+
+        let f13 = Config::new(vec![(
+            "macro",
+            Item::new(vec!["f13".into()], String::new()),
+        )]);
+        assert_eq!(
+            f13.export(Target::Tmux("root")),
+            Err(ExportError::UnsupportedKey {
+                key: "f13".to_string(),
+                command: "macro".to_string(),
+            })
+        );
+        assert!(f13.export(Target::Vim("normal")).is_ok());
+
+        let shifted = Config::new(vec![
+            ("append", Item::new(vec!["shift-a".into()], String::new())),
+            ("bang", Item::new(vec!["shift-1".into()], String::new())),
+        ]);
+        let append = Config::new(vec![shifted.items[0].clone()]);
+        assert_eq!(
+            append.export(Target::Helix("normal")).unwrap(),
+            "[keys.normal]\nA = \"append\"\n"
+        );
+        assert_eq!(
+            shifted.export(Target::Helix("normal")),
+            Err(ExportError::UnsupportedKey {
+                key: "shift-1".to_string(),
+                command: "bang".to_string(),
+            })
+        );
+
+        let states = Config::new(vec![
+            (
+                "charge",
+                Item::new(vec!["press-space".into()], String::new()),
+            ),
+            ("jump", Item::new(vec!["space:up".into()], String::new())),
+        ]);
+        let charge = Config::new(vec![states.items[0].clone()]);
+        assert_eq!(
+            charge.export(Target::Vim("normal")).unwrap(),
+            "nnoremap <Space> charge\n"
+        );
+        for target in [
+            Target::VsCode,
+            Target::Helix("normal"),
+            Target::Tmux("root"),
+            Target::Vim("normal"),
+        ] {
+            assert_eq!(
+                states.export(target),
+                Err(ExportError::UnsupportedKey {
+                    key: "space:up".to_string(),
+                    command: "jump".to_string(),
+                }),
+                "{target:?}"
+            );
+        }
+
+        // Keys added to `items` directly are not validated
+        let mut invalid = Config::new(vec![(
+            "save",
+            Item::new(vec!["ctrl-s".into()], String::new()),
+        )]);
+        invalid.items[0].1.keys.push("ctrl-ss".into());
+        for target in [
+            Target::VsCode,
+            Target::Helix("normal"),
+            Target::Tmux("root"),
+            Target::Vim("normal"),
+        ] {
+            assert_eq!(
+                invalid.export(target),
+                Err(ExportError::UnsupportedKey {
+                    key: "ctrl-ss".to_string(),
+                    command: "save".to_string(),
+                }),
+                "{target:?}"
+            );
+        }
+
+        assert!(config().export(Target::VsCode).is_ok());
+        assert_eq!(
+            config().export(Target::Vim("normal")),
+            Err(ExportError::UnsupportedWhen {
+                key: "ctrl-k ctrl-c".to_string(),
+                command: "comment".to_string(),
+            })
+        );
+    }
+}
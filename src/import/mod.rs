@@ -0,0 +1,56 @@
+//! # Importers
+//!
+//! Importers translate the key bindings of other applications into keymap
+//! configs, reporting the bindings that have no equivalent instead of failing.
+use std::fmt;
+
+pub mod vim;
+pub mod vscode;
+
+/// The result of an import: the translated config and the bindings that
+/// could not be translated.
+#[derive(Debug)]
+pub struct Import<C> {
+    /// The translated bindings.
+    pub config: C,
+    /// The bindings that were left out, in input order.
+    pub skipped: Vec<Skipped>,
+}
+
+/// A binding that could not be imported.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Skipped {
+    /// The key as written in the source (e.g. `"ctrl+k ctrl+c"`).
+    pub key: String,
+    /// The command bound to the key.
+    pub command: String,
+    /// Why the binding was left out.
+    pub reason: Unsupported,
+}
+
+/// Why a binding could not be imported.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Unsupported {
+    /// A key (or modifier) with no keymap equivalent (e.g. `abnt_c1`).
+    Key(String),
+    /// A condition the [`when`](crate::when) grammar cannot express
+    /// (e.g. `resourceExtname =~ /rs/`).
+    When(String),
+    /// Removes a binding that is not part of the import, such as a default
+    /// binding of the source application.
+    Removal,
+    /// Passes arguments to the command, which an item cannot express.
+    Args,
+}
+
+impl fmt::Display for Skipped {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} ({}): ", self.key, self.command)?;
+        match &self.reason {
+            Unsupported::Key(key) => write!(f, "unsupported key {key:?}"),
+            Unsupported::When(when) => write!(f, "unsupported condition {when:?}"),
+            Unsupported::Removal => write!(f, "removes a binding that was not imported"),
+            Unsupported::Args => write!(f, "passes arguments to the command"),
+        }
+    }
+}
@@ -0,0 +1,456 @@
+//! # Vim
+//!
+//! Imports the mapping commands of a vimrc (or `init.vim`) into one
+//! [`Config<String>`] per mode, keyed by the right-hand side of the mapping:
+//!
+//! ```vim
+//! let mapleader = ","
+//! nnoremap <silent> <leader>ff :Files<CR>
+//! map <C-a> ggVG
+//! inoremap jk <Esc>
+//! ```
+//!
+//! Key notation such as `<C-x>`, `<M-x>`, `<S-Tab>`, `<CR>`, `<Esc>` and
+//! `<F5>` is translated into key expressions, and `<leader>` is expanded with
+//! the `mapleader` set so far (`\` by default). Lines other than mapping
+//! commands are ignored.
+//!
+//! Modes are named `normal`, `visual`, `select`, `operator`, `insert`,
+//! `command` and `terminal`, and added in order of first use.
+use crate::{
+    import::{Import, Skipped, Unsupported},
+    parser::parse,
+    Config, Item, ModalConfig,
+};
+
+const NORMAL: &str = "normal";
+const VISUAL: &str = "visual";
+const SELECT: &str = "select";
+const OPERATOR: &str = "operator";
+const INSERT: &str = "insert";
+const COMMAND: &str = "command";
+const TERMINAL: &str = "terminal";
+
+/// Special arguments that may precede the left-hand side of a mapping.
+const ARGUMENTS: [&str; 7] = [
+    "<buffer>",
+    "<nowait>",
+    "<silent>",
+    "<special>",
+    "<script>",
+    "<expr>",
+    "<unique>",
+];
+
+/// Imports the mappings of a vimrc.
+///
+/// # Example
+///
+/// ```
+/// let import = keymap::import::vim::import(r#"
+///     let mapleader = " "
+///     nnoremap <leader>ff :Files<CR>
+///     nmap <Plug>(easymotion) <Nop>
+/// "#);
+///
+/// let normal = import.config.config(&"normal".to_string()).unwrap();
+/// let (command, _) = normal.get_item_by_key_str("space f f").unwrap();
+/// assert_eq!(command, ":Files<CR>");
+/// assert_eq!(import.skipped[0].key, "<Plug>(easymotion)");
+/// ```
+pub fn import(source: &str) -> Import<ModalConfig<String, String>> {
+    let mut leader = "\\".to_string();
+    let mut local_leader = "\\".to_string();
+    // (mode, rhs, keys) in order of first appearance
+    let mut entries: Vec<(&str, String, Vec<String>)> = Vec::new();
+    let mut skipped = Vec::new();
+
+    for line in source.lines() {
+        let line = line.trim_start().trim_start_matches(':');
+        let (command, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+
+        if command == "let" {
+            match rest.split_once('=') {
+                Some((name, value)) if is_leader(name, "mapleader") => leader = string(value),
+                Some((name, value)) if is_leader(name, "maplocalleader") => {
+                    local_leader = string(value)
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        let (modes, unmap) = match map_modes(command) {
+            Some(modes) => (modes, false),
+            None => match unmap_modes(command) {
+                Some(modes) => (modes, true),
+                None => continue,
+            },
+        };
+
+        let (lhs, rhs) = split_mapping(rest);
+        if lhs.is_empty() || (!unmap && rhs.is_empty()) {
+            continue;
+        }
+
+        let mut skip = |reason| {
+            skipped.push(Skipped {
+                key: lhs.to_string(),
+                command: rhs.to_string(),
+                reason,
+            })
+        };
+
+        let key = match translate(lhs, &leader, &local_leader) {
+            Ok(key) => key,
+            Err(part) => {
+                skip(Unsupported::Key(part));
+                continue;
+            }
+        };
+
+        if unmap {
+            let mut removed = false;
+            for (_, _, keys) in entries.iter_mut().filter(|(m, _, _)| modes.contains(m)) {
+                let len = keys.len();
+                keys.retain(|k| *k != key);
+                removed |= keys.len() < len;
+            }
+
+            if !removed {
+                skip(Unsupported::Removal);
+            }
+            continue;
+        }
+
+        for &mode in modes {
+            // A later mapping of the same keys replaces the earlier one
+            for (_, _, keys) in entries.iter_mut().filter(|(m, _, _)| *m == mode) {
+                keys.retain(|k| *k != key);
+            }
+
+            match entries.iter_mut().find(|(m, r, _)| *m == mode && r == rhs) {
+                Some((_, _, keys)) => keys.push(key.clone()),
+                None => entries.push((mode, rhs.to_string(), vec![key.clone()])),
+            }
+        }
+    }
+
+    let mut config = ModalConfig::new();
+    let mut modes = Vec::new();
+    for (mode, _, _) in &entries {
+        if !modes.contains(mode) {
+            modes.push(*mode);
+        }
+    }
+    for mode in modes {
+        let items = entries
+            .iter()
+            .filter(|(m, _, keys)| *m == mode && !keys.is_empty())
+            .map(|(_, rhs, keys)| (rhs.clone(), Item::new(keys.clone(), String::new())))
+            .collect();
+        config.insert(mode.to_string(), Config::new(items));
+    }
+
+    Import { config, skipped }
+}
+
+/// Returns the modes of a mapping command (e.g. `nnoremap`).
+fn map_modes(command: &str) -> Option<&'static [&'static str]> {
+    Some(match command {
+        "map" | "no" | "noremap" => &[NORMAL, VISUAL, SELECT, OPERATOR],
+        "nm" | "nmap" | "nn" | "nno" | "nnoremap" => &[NORMAL],
+        "vm" | "vmap" | "vn" | "vno" | "vnoremap" => &[VISUAL, SELECT],
+        "xm" | "xmap" | "xn" | "xno" | "xnoremap" => &[VISUAL],
+        "smap" | "snor" | "snoremap" => &[SELECT],
+        "om" | "omap" | "ono" | "onoremap" => &[OPERATOR],
+        "im" | "imap" | "ino" | "inoremap" => &[INSERT],
+        "cm" | "cmap" | "cno" | "cnoremap" => &[COMMAND],
+        "tma" | "tmap" | "tno" | "tnoremap" => &[TERMINAL],
+        "map!" | "no!" | "noremap!" => &[INSERT, COMMAND],
+        _ => return None,
+    })
+}
+
+/// Returns the modes of an unmapping command (e.g. `nunmap`).
+fn unmap_modes(command: &str) -> Option<&'static [&'static str]> {
+    Some(match command {
+        "unm" | "unmap" => &[NORMAL, VISUAL, SELECT, OPERATOR],
+        "nun" | "nunmap" => &[NORMAL],
+        "vu" | "vunmap" => &[VISUAL, SELECT],
+        "xu" | "xunmap" => &[VISUAL],
+        "sunm" | "sunmap" => &[SELECT],
+        "ou" | "ounmap" => &[OPERATOR],
+        "iu" | "iunmap" => &[INSERT],
+        "cu" | "cunmap" => &[COMMAND],
+        "tunma" | "tunmap" => &[TERMINAL],
+        "unm!" | "unmap!" => &[INSERT, COMMAND],
+        _ => return None,
+    })
+}
+
+/// Splits the arguments of a mapping command into its left- and right-hand
+/// sides, dropping special arguments such as `<silent>`.
+fn split_mapping(mut rest: &str) -> (&str, &str) {
+    loop {
+        rest = rest.trim_start();
+        match ARGUMENTS.iter().find(|arg| {
+            rest.get(..arg.len())
+                .is_some_and(|s| s.eq_ignore_ascii_case(arg))
+        }) {
+            Some(arg) => rest = &rest[arg.len()..],
+            None => break,
+        }
+    }
+
+    let (lhs, rhs) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+
+    // An unescaped `|` ends the command
+    let mut end = rhs.len();
+    let mut prev = None;
+    for (i, ch) in rhs.char_indices() {
+        if ch == '|' && prev != Some('\\') && prev != Some('\u{16}') {
+            end = i;
+            break;
+        }
+        prev = Some(ch);
+    }
+
+    (lhs, rhs[..end].trim())
+}
+
+/// Returns `true` if `name` is the (optionally `g:` scoped) variable `var`.
+fn is_leader(name: &str, var: &str) -> bool {
+    let name = name.trim();
+    name.strip_prefix("g:").unwrap_or(name) == var
+}
+
+/// Reads a vimscript string literal such as `","` or `"\<Space>"`.
+fn string(value: &str) -> String {
+    let value = value.trim();
+    let inner = value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .map(|v| match v {
+            "\\\\" => "\\",
+            _ => v
+                .strip_prefix('\\')
+                .filter(|v| v.starts_with('<'))
+                .unwrap_or(v),
+        })
+        .or_else(|| value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')))
+        .unwrap_or(value);
+
+    inner.to_string()
+}
+
+/// Translates a left-hand side (e.g. `<leader>f<C-x>`) into a key sequence
+/// (e.g. `"\\ f ctrl-x"`), or returns the part that has no equivalent.
+fn translate(lhs: &str, leader: &str, local_leader: &str) -> Result<String, String> {
+    let mut keys = Vec::new();
+    let mut rest = lhs;
+
+    while let Some(ch) = rest.chars().next() {
+        let notation = rest
+            .strip_prefix('<')
+            .and_then(|r| r.split_once('>'))
+            .map(|(inner, _)| inner)
+            .filter(|inner| {
+                !inner.is_empty() && inner.chars().all(|c| c.is_ascii_graphic() && c != '<')
+            });
+
+        match notation {
+            Some(inner) => {
+                let part = &rest[..inner.len() + 2];
+                match inner.to_ascii_lowercase().as_str() {
+                    "leader" if !leader.is_empty() => {
+                        keys.push(translate(leader, "", "").map_err(|_| part.to_string())?);
+                    }
+                    "localleader" if !local_leader.is_empty() => {
+                        keys.push(translate(local_leader, "", "").map_err(|_| part.to_string())?);
+                    }
+                    _ => keys.push(translate_notation(inner).ok_or_else(|| part.to_string())?),
+                }
+                rest = &rest[part.len()..];
+            }
+            None => {
+                let key = match ch {
+                    ' ' => "space".to_string(),
+                    _ => ch.to_string(),
+                };
+                keys.push(canonical(&key).ok_or_else(|| ch.to_string())?);
+                rest = &rest[ch.len_utf8()..];
+            }
+        }
+    }
+
+    Ok(keys.join(" "))
+}
+
+/// Translates the inside of a `<...>` notation (e.g. `C-S-x`, `CR`).
+fn translate_notation(inner: &str) -> Option<String> {
+    let mut expr = String::new();
+    let mut rest = inner;
+    let mut ctrl = false;
+
+    while let Some((modifier, name)) = rest.split_once('-').filter(|(_, name)| !name.is_empty()) {
+        expr.push_str(match modifier.to_ascii_lowercase().as_str() {
+            "c" => {
+                ctrl = true;
+                "ctrl-"
+            }
+            "m" | "a" => "alt-",
+            "s" => "shift-",
+            "d" => "cmd-",
+            _ => return None,
+        });
+        rest = name;
+    }
+
+    let name = rest.to_ascii_lowercase();
+    let key = match name.as_str() {
+        _ if rest.chars().count() == 1 && ctrl => name.as_str(),
+        _ if rest.chars().count() == 1 => rest,
+        "cr" | "enter" | "return" => "enter",
+        "esc" => "esc",
+        "tab" if expr.ends_with("shift-") => {
+            expr.truncate(expr.len() - "shift-".len());
+            "backtab"
+        }
+        "tab" => "tab",
+        "bs" => "backspace",
+        "del" => "delete",
+        "space" => "space",
+        "insert" => "insert",
+        "home" => "home",
+        "end" => "end",
+        "pageup" => "pageup",
+        "pagedown" => "pagedown",
+        "up" => "up",
+        "down" => "down",
+        "left" => "left",
+        "right" => "right",
+        "lt" => "<",
+        "bar" => "|",
+        "bslash" => "\\",
+        "kenter" => "kpenter",
+        "kplus" => "kpplus",
+        "kminus" => "kpminus",
+        "kmultiply" => "kpmultiply",
+        "kdivide" => "kpdivide",
+        "kpoint" => "kpdecimal",
+        _ if name.len() == 2 && name.starts_with('k') && name.as_bytes()[1].is_ascii_digit() => {
+            expr.push_str("kp");
+            &name[1..]
+        }
+        _ if name.starts_with('f') && name[1..].chars().all(|c| c.is_ascii_digit()) => {
+            name.as_str()
+        }
+        _ => return None,
+    };
+    expr.push_str(key);
+
+    canonical(&expr)
+}
+
+/// Parses a key expression and formats it in canonical form.
+fn canonical(expr: &str) -> Option<String> {
+    parse(expr).ok().map(|node| node.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_translate() {
+        [
+            ("<leader>ff", Ok(", f f")),
+            ("<C-x>", Ok("ctrl-x")),
+            ("<C-X>", Ok("ctrl-x")),
+            ("<M-x>", Ok("alt-x")),
+            ("<A-S-x>", Ok("alt-shift-x")),
+            ("<S-Tab>", Ok("backtab")),
+            ("<CR>", Ok("enter")),
+            ("<esc>", Ok("esc")),
+            ("<F5>", Ok("f5")),
+            ("<C-w><Bar>", Ok("ctrl-w |")),
+            ("gT", Ok("g T")),
+            ("< ", Ok("< space")),
+            ("<localleader>r", Ok("\\ r")),
+            ("<Plug>(foo)", Err("<Plug>")),
+            ("<F20>", Ok("f20")),
+            ("<F36>", Err("<F36>")),
+            ("<C-k5><kEnter>", Ok("ctrl-kp5 kpenter")),
+        ]
+        .into_iter()
+        .for_each(|(lhs, expected)| {
+            assert_eq!(
+                translate(lhs, ",", "\\"),
+                expected.map(str::to_string).map_err(str::to_string),
+                "{lhs}"
+            );
+        });
+    }
+
+    #[test]
+    fn test_import() {
+        let import = import(
+            r#"
+            " Mappings
+            set nocompatible
+            let g:mapleader = "\<Space>"
+            nnoremap <silent> <leader>ff :Files<CR>
+            nnoremap <leader>fg :Files<CR>
+            map <C-a> ggVG
+            inoremap jk <Esc>
+            :nmap <C-a> :echo 1 | echo 2
+            vunmap <C-a>
+            nunmap Q
+            xnoremap <Plug>(x) y
+            "#,
+        );
+
+        let modes = import.config.modes().collect::<Vec<_>>();
+        assert_eq!(
+            modes,
+            vec!["normal", "visual", "select", "operator", "insert"]
+        );
+
+        let normal = import.config.config(&NORMAL.to_string()).unwrap();
+        let items = normal
+            .items
+            .iter()
+            .map(|(rhs, item)| (rhs.as_str(), item.keys.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            items,
+            vec![
+                (":Files<CR>", vec!["space f f".into(), "space f g".into()]),
+                (":echo 1", vec!["ctrl-a".into()]),
+            ]
+        );
+
+        // `vunmap` leaves the mapping of the other modes in place
+        let visual = import.config.config(&VISUAL.to_string()).unwrap();
+        assert!(visual.items.is_empty());
+        let operator = import.config.config(&OPERATOR.to_string()).unwrap();
+        assert_eq!(operator.get_item_by_key_str("ctrl-a").unwrap().0, "ggVG");
+
+        let insert = import.config.config(&INSERT.to_string()).unwrap();
+        assert_eq!(insert.get_item_by_key_str("j k").unwrap().0, "<Esc>");
+
+        let skipped = import
+            .skipped
+            .iter()
+            .map(|skipped| (skipped.key.as_str(), &skipped.reason))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            skipped,
+            vec![
+                ("Q", &Unsupported::Removal),
+                ("<Plug>(x)", &Unsupported::Key("<Plug>".to_string())),
+            ]
+        );
+    }
+}
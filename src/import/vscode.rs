@@ -0,0 +1,436 @@
+//! # VS Code
+//!
+//! Imports the bindings of a VS Code `keybindings.json` file:
+//!
+//! ```json
+//! [
+//!     { "key": "ctrl+k ctrl+c", "command": "editor.action.addCommentLine", "when": "editorTextFocus" },
+//!     { "key": "ctrl+shift+k", "command": "-editor.action.deleteLines" }
+//! ]
+//! ```
+//!
+//! Chords (`"ctrl+k ctrl+c"`) become key sequences (`"ctrl-k ctrl-c"`), and
+//! `cmd`, `meta` and `win` all map to the `cmd` modifier. Bindings are
+//! grouped into one [`Item`] per command and `when` clause, keyed by the
+//! command name. A command prefixed with `-` removes a preceding binding.
+//!
+//! A command bound under several `when` clauses yields several items for the
+//! same command, which [`Config`] lookups handle but a TOML or JSON map
+//! cannot hold. Bindings with `args` are skipped, since an [`Item`] cannot
+//! pass them on to the command.
+use serde::{de::IgnoredAny, Deserialize, Deserializer};
+
+use crate::{
+    import::{Import, Skipped, Unsupported},
+    parser::parse,
+    when::{self, Expr},
+    Config, Item,
+};
+
+/// A single entry of `keybindings.json`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Binding {
+    /// The key, e.g. `"ctrl+k ctrl+c"`.
+    #[serde(default)]
+    pub key: String,
+    /// The command, prefixed with `-` to remove a binding.
+    pub command: String,
+    /// The condition under which the binding applies.
+    pub when: Option<String>,
+    /// Whether the binding passes `args` to the command.
+    #[serde(default, rename = "args", deserialize_with = "present")]
+    pub has_args: bool,
+}
+
+/// Deserializes any value as `true`, so a field is `true` when present.
+fn present<'de, D: Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {
+    IgnoredAny::deserialize(deserializer).map(|_| true)
+}
+
+/// Imports the bindings of a `keybindings.json` file.
+///
+/// Comments and trailing commas are accepted, as in VS Code.
+///
+/// # Errors
+///
+/// Returns an error if the input is not an array of bindings.
+///
+/// # Example
+///
+/// ```
+/// let import = keymap::import::vscode::from_json(r#"[
+///     // Toggle comments
+///     { "key": "ctrl+k ctrl+c", "command": "editor.action.addCommentLine" },
+///     { "key": "abnt_c1", "command": "editor.action.transform" },
+/// ]"#).unwrap();
+///
+/// let (command, _) = import.config.get_item_by_key_str("ctrl-k ctrl-c").unwrap();
+/// assert_eq!(command, "editor.action.addCommentLine");
+/// assert_eq!(import.skipped[0].key, "abnt_c1");
+/// ```
+#[cfg(feature = "vscode")]
+pub fn from_json(json: &str) -> Result<Import<Config<String>>, serde_json::Error> {
+    let bindings: Vec<Binding> = serde_json::from_str(&strip_jsonc(json))?;
+    Ok(import(bindings))
+}
+
+/// Imports bindings, e.g. deserialized from `keybindings.json` by other
+/// means than `from_json`.
+pub fn import<I: IntoIterator<Item = Binding>>(bindings: I) -> Import<Config<String>> {
+    // (command, when, keys) in order of first appearance
+    let mut entries: Vec<(String, Option<Expr>, Vec<String>)> = Vec::new();
+    let mut skipped = Vec::new();
+
+    for binding in bindings {
+        let mut skip = |reason| {
+            skipped.push(Skipped {
+                key: binding.key.clone(),
+                command: binding.command.clone(),
+                reason,
+            })
+        };
+
+        let when = match binding.when.as_deref().map(str::trim) {
+            Some(when) if !when.is_empty() => match when::parse(when) {
+                Ok(expr) => Some(expr),
+                Err(_) => {
+                    skip(Unsupported::When(when.to_string()));
+                    continue;
+                }
+            },
+            _ => None,
+        };
+
+        let key = if binding.key.trim().is_empty() {
+            None
+        } else {
+            match translate(&binding.key) {
+                Ok(key) => Some(key),
+                Err(part) => {
+                    skip(Unsupported::Key(part));
+                    continue;
+                }
+            }
+        };
+
+        if let Some(command) = binding.command.strip_prefix('-') {
+            let mut removed = false;
+            for (_, _, keys) in entries
+                .iter_mut()
+                .filter(|(c, w, _)| c == command && (when.is_none() || *w == when))
+            {
+                let len = keys.len();
+                keys.retain(|k| key.as_ref().is_some_and(|key| k != key));
+                removed |= keys.len() < len;
+            }
+
+            if !removed {
+                skip(Unsupported::Removal);
+            }
+            continue;
+        }
+
+        let Some(key) = key else {
+            skip(Unsupported::Key(binding.key.clone()));
+            continue;
+        };
+
+        if binding.has_args {
+            skip(Unsupported::Args);
+            continue;
+        }
+
+        match entries
+            .iter_mut()
+            .find(|(c, w, _)| *c == binding.command && *w == when)
+        {
+            Some((_, _, keys)) if keys.contains(&key) => {}
+            Some((_, _, keys)) => keys.push(key),
+            None => entries.push((binding.command, when, vec![key])),
+        }
+    }
+
+    let items = entries
+        .into_iter()
+        .filter(|(_, _, keys)| !keys.is_empty())
+        .map(|(command, when, keys)| (command, Item::new(keys, String::new()).with_when(when)))
+        .collect();
+
+    Import {
+        config: Config::new(items),
+        skipped,
+    }
+}
+
+/// Translates a VS Code key (e.g. `"ctrl+k ctrl+c"`) into a key sequence
+/// (e.g. `"ctrl-k ctrl-c"`), or returns the chord that has no equivalent.
+fn translate(key: &str) -> Result<String, String> {
+    key.split_whitespace()
+        .map(|chord| translate_chord(chord).ok_or_else(|| chord.to_string()))
+        .collect::<Result<Vec<_>, _>>()
+        .map(|chords| chords.join(" "))
+}
+
+fn translate_chord(chord: &str) -> Option<String> {
+    let chord = chord.to_lowercase();
+    let (modifiers, key) = match chord.strip_suffix("++") {
+        Some(modifiers) => (modifiers, "+"),
+        None if chord == "+" => ("", "+"),
+        None => chord.rsplit_once('+').unwrap_or(("", &chord)),
+    };
+
+    let mut expr = String::new();
+    for modifier in modifiers.split('+').filter(|m| !m.is_empty()) {
+        expr.push_str(match modifier {
+            "ctrl" => "ctrl-",
+            "shift" => "shift-",
+            "alt" | "option" => "alt-",
+            "cmd" | "meta" | "win" => "cmd-",
+            _ => return None,
+        });
+    }
+    let key = translate_key(key);
+    match key.strip_prefix("numpad").filter(|digit| digit.len() == 1) {
+        Some(digit) => {
+            expr.push_str("kp");
+            expr.push_str(digit);
+        }
+        None => expr.push_str(key),
+    }
+
+    // Formatting the parsed node yields the canonical form (e.g. "del" -> "delete")
+    parse(&expr).ok().map(|node| node.to_string())
+}
+
+/// Maps a VS Code key name (including scan codes like `[KeyA]`) to a key name.
+fn translate_key(key: &str) -> &str {
+    if let Some(code) = key.strip_prefix('[').and_then(|k| k.strip_suffix(']')) {
+        return match code {
+            "arrowup" => "up",
+            "arrowdown" => "down",
+            "arrowleft" => "left",
+            "arrowright" => "right",
+            _ => code
+                .strip_prefix("key")
+                .or_else(|| code.strip_prefix("digit"))
+                .filter(|c| c.len() == 1)
+                .unwrap_or_else(|| translate_key(code)),
+        };
+    }
+
+    match key {
+        "escape" => "esc",
+        "pausebreak" => "pause",
+        "contextmenu" => "menu",
+        "numpad_add" => "kpplus",
+        "numpad_subtract" => "kpminus",
+        "numpad_multiply" => "kpmultiply",
+        "numpad_divide" => "kpdivide",
+        "numpad_decimal" => "kpdecimal",
+        "numpad_separator" => ",",
+        "mediaplaypause" => "playpause",
+        "mediastop" => "stop",
+        "mediatracknext" => "nexttrack",
+        "mediatrackprevious" => "prevtrack",
+        "audiovolumeup" => "volumeup",
+        "audiovolumedown" => "volumedown",
+        "audiovolumemute" => "mute",
+        "oem_1" => ";",
+        "oem_plus" => "=",
+        "oem_comma" => ",",
+        "oem_minus" => "-",
+        "oem_period" => ".",
+        "oem_2" => "/",
+        "oem_3" => "`",
+        "oem_4" => "[",
+        "oem_5" => "\\",
+        "oem_6" => "]",
+        "oem_7" => "'",
+        _ => key,
+    }
+}
+
+/// Removes the comments and trailing commas that VS Code allows in JSON.
+#[cfg(feature = "vscode")]
+fn strip_jsonc(input: &str) -> String {
+    let mut output = String::with_capacity(input.len());
+    let mut chars = input.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '"' => {
+                output.push(ch);
+                while let Some(ch) = chars.next() {
+                    output.push(ch);
+                    match ch {
+                        '\\' => output.extend(chars.next()),
+                        '"' => break,
+                        _ => {}
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'/') => while chars.next_if(|&ch| ch != '\n').is_some() {},
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut prev = None;
+                for ch in chars.by_ref() {
+                    if prev == Some('*') && ch == '/' {
+                        break;
+                    }
+                    prev = Some(ch);
+                }
+            }
+            ']' | '}' => {
+                // Drop a trailing comma before the closing bracket
+                let end = output.trim_end().len();
+                if output[..end].ends_with(',') {
+                    output.truncate(end - 1);
+                }
+                output.push(ch);
+            }
+            _ => output.push(ch),
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn binding(key: &str, command: &str, when: Option<&str>) -> Binding {
+        Binding {
+            key: key.to_string(),
+            command: command.to_string(),
+            when: when.map(str::to_string),
+            has_args: false,
+        }
+    }
+
+    #[test]
+    fn test_translate() {
+        [
+            ("ctrl+k ctrl+c", Ok("ctrl-k ctrl-c")),
+            ("Ctrl+Shift+P", Ok("ctrl-shift-p")),
+            ("meta+alt+pageup", Ok("alt-cmd-pageup")),
+            ("win+escape", Ok("cmd-esc")),
+            ("ctrl+numpad0", Ok("ctrl-kp0")),
+            ("numpad_add", Ok("kpplus")),
+            ("audiovolumemute", Ok("mute")),
+            ("pausebreak", Ok("pause")),
+            ("ctrl++", Ok("ctrl-+")),
+            ("ctrl+-", Ok("ctrl--")),
+            ("shift+oem_5", Ok("shift-\\")),
+            ("ctrl+[KeyA]", Ok("ctrl-a")),
+            ("[ArrowUp]", Ok("up")),
+            ("delete", Ok("delete")),
+            ("ctrl+k capslock", Ok("ctrl-k capslock")),
+            ("ctrl+k abnt_c1", Err("abnt_c1")),
+            ("hyper+a", Err("hyper+a")),
+        ]
+        .into_iter()
+        .for_each(|(key, expected)| {
+            assert_eq!(
+                translate(key),
+                expected.map(str::to_string).map_err(str::to_string),
+                "{key}"
+            );
+        });
+    }
+
+    #[test]
+    fn test_import() {
+        let import = import([
+            binding("ctrl+k ctrl+c", "comment", Some("editorTextFocus")),
+            binding("ctrl+/", "comment", Some("editorTextFocus")),
+            binding("ctrl+/", "comment", Some("editorTextFocus")),
+            binding("ctrl+s", "save", None),
+            binding("ctrl+shift+s", "save", None),
+            binding("ctrl+shift+s", "-save", None),
+            binding("ctrl+d", "-editor.action.addSelection", None),
+            binding("f5", "run", Some("resourceExtname == .rs")),
+            binding("f5", "run", Some("resourceExtname =~ /rs/")),
+            binding("abnt_c1", "debug", None),
+            binding("ctrl+alt+c", "comment", None),
+            Binding {
+                has_args: true,
+                ..binding("ctrl+i", "type", None)
+            },
+        ]);
+
+        let items = import
+            .config
+            .items
+            .iter()
+            .map(|(command, item)| (command.as_str(), item.keys.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            items,
+            vec![
+                (
+                    "comment",
+                    vec!["ctrl-k ctrl-c".to_string(), "ctrl-/".to_string()]
+                ),
+                ("save", vec!["ctrl-s".to_string()]),
+                ("run", vec!["f5".to_string()]),
+                ("comment", vec!["alt-ctrl-c".to_string()]),
+            ]
+        );
+        let when = |index: usize| {
+            import.config.items[index]
+                .1
+                .when
+                .as_ref()
+                .map(|w| w.to_string())
+        };
+        assert_eq!(when(0).as_deref(), Some("editorTextFocus"));
+        assert_eq!(when(2).as_deref(), Some("resourceExtname == .rs"));
+        assert_eq!(when(3), None);
+
+        let reasons = import
+            .skipped
+            .iter()
+            .map(|skipped| &skipped.reason)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            reasons,
+            vec![
+                &Unsupported::Removal,
+                &Unsupported::When("resourceExtname =~ /rs/".to_string()),
+                &Unsupported::Key("abnt_c1".to_string()),
+                &Unsupported::Args,
+            ]
+        );
+
+        // The item whose clause holds wins over the one without a clause
+        let (command, _) = import
+            .config
+            .get_item_by_keymaps_with(
+                &crate::parser::parse_seq("ctrl-/").unwrap(),
+                &["editorTextFocus"][..],
+            )
+            .unwrap();
+        assert_eq!(command, "comment");
+        assert!(import.config.get_item_by_key_str("ctrl-/").is_none());
+    }
+
+    #[cfg(feature = "vscode")]
+    #[test]
+    fn test_from_json() {
+        let import = from_json(
+            r#"// Place your key bindings in this file
+            [
+                /* comment "lines" */
+                { "key": "ctrl+k ctrl+c", "command": "comment", "args": { "text": "//" } },
+                { "key": "ctrl+s", "command": "save", },
+            ]"#,
+        )
+        .unwrap();
+
+        assert_eq!(import.config.items.len(), 1);
+        assert_eq!(import.config.items[0].0, "save");
+        assert_eq!(import.skipped[0].key, "ctrl+k ctrl+c");
+        assert_eq!(import.skipped[0].reason, Unsupported::Args);
+    }
+}
@@ -104,7 +104,7 @@ pub trait KeyGroupValue: Default {
 
 impl KeyGroupValue for char {
     fn from_keymap_node(node: &KeyMap) -> Self {
-        match node.key {
+        match node.key.main_key().unwrap_or_else(|| node.key.clone()) {
             Key::Char(c) => c,
             Key::Space => ' ',
             _ => '\0',
@@ -117,7 +117,7 @@ macro_rules! impl_key_group_value_uint {
         $(
             impl KeyGroupValue for $t {
                 fn from_keymap_node(node: &KeyMap) -> Self {
-                    match node.key {
+                    match node.key.main_key().unwrap_or_else(|| node.key.clone()) {
                         Key::Char(c) => c.to_digit(10).unwrap_or(0) as $t,
                         _ => 0,
                     }
@@ -0,0 +1,494 @@
+//! Layered configuration with provenance tracking.
+//!
+//! Applications often read key bindings from several places, e.g. the
+//! defaults from `#[derive(KeyMap)]`, then a system file, a user file and a
+//! per-project file. [`LayeredConfig`] applies each layer in order with the
+//! same override rules as [`DerivedConfig`](crate::DerivedConfig), and
+//! remembers which layer every item and key came from.
+//!
+//! ```ignore
+//! let mut config = LayeredConfig::<Action>::derived();
+//! config.push("/etc/app/keys.toml", toml::Deserializer::parse(&system)?)?;
+//! config.push("~/.config/app/keys.toml", toml::Deserializer::parse(&user)?)?;
+//!
+//! // Why is ctrl-s bound to this?
+//! if let Some(p) = config.explain_key_str("ctrl-s") {
+//!     println!("{:?} ({}) was bound in {}", p.value, p.key, p.key_layer);
+//! }
+//! ```
+use std::{fmt, marker::PhantomData, ops::Deref};
+
+use keymap_parser::{parse_seq, when::Context};
+use serde::{
+    de::{self, MapAccess, Visitor},
+    Deserialize, Deserializer,
+};
+
+use crate::{
+    config::{is_same_key, EntryRaw},
+    keymap::ToKeyMap,
+    matcher::Matcher,
+    Config, Item, KeyMap, KeyMapConfig,
+};
+
+/// A [`Config<T>`] built from an ordered stack of layers, where later layers
+/// override earlier ones.
+///
+/// Each layer has a name (typically a file path), which is recorded for
+/// every item and every key it binds. See [`LayeredConfig::explain`].
+///
+/// # Example
+///
+/// ```
+/// # use keymap::{Config, Item, LayeredConfig};
+/// let defaults = Config::new(vec![
+///     ("Save".to_string(), Item::new(vec!["ctrl-s".into()], "Save".into())),
+///     ("Quit".to_string(), Item::new(vec!["q".into()], "Quit".into())),
+/// ]);
+///
+/// let mut config = LayeredConfig::new("default", defaults);
+/// config
+///     .push("~/.config/app/keys.toml", toml::Deserializer::parse(r#"
+///         Quit = { add_keys = ["esc"] }
+///     "#).unwrap())
+///     .unwrap();
+///
+/// let p = config.explain_key_str("esc").unwrap();
+/// assert_eq!(p.value, "Quit");
+/// assert_eq!(p.key_layer, "~/.config/app/keys.toml");
+///
+/// let p = config.explain_key_str("q").unwrap();
+/// assert_eq!(p.key_layer, "default");
+/// ```
+#[derive(Debug)]
+pub struct LayeredConfig<T> {
+    config: Config<T>,
+
+    /// The layer names in the order they were applied.
+    layers: Vec<String>,
+
+    /// The origin of each item, parallel to `config.items`.
+    origins: Vec<Origin>,
+
+    /// Maps each key pattern to the `(item index, key index)` of every item
+    /// bound to it, mirroring the matcher of `config` so the matched key
+    /// expression can be reported.
+    matcher: Matcher<Entries>,
+}
+
+/// The `(item index, key index)` of every item bound to a key pattern.
+type Entries = Vec<(usize, usize)>;
+
+/// Layer indexes of an item and of each of its keys.
+#[derive(Debug, Clone)]
+struct Origin {
+    layer: usize,
+    keys: Vec<usize>,
+}
+
+/// Describes where a matched binding of a [`LayeredConfig`] comes from.
+#[derive(Debug, PartialEq)]
+pub struct Provenance<'a, T> {
+    /// The matched value (e.g. an action).
+    pub value: &'a T,
+    /// The item of the matched value.
+    pub item: &'a Item,
+    /// The key expression that matched, as written in `item.keys`.
+    pub key: &'a str,
+    /// The layer that bound `key`.
+    pub key_layer: &'a str,
+    /// The layer that last changed `item`.
+    pub item_layer: &'a str,
+}
+
+// Manual impls avoid requiring `T: Clone`/`T: Copy`.
+impl<T> Clone for Provenance<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Provenance<'_, T> {}
+
+impl<T> Deref for LayeredConfig<T> {
+    type Target = Config<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.config
+    }
+}
+
+impl<T: KeyMapConfig<T>> LayeredConfig<T> {
+    /// Creates a `LayeredConfig` whose base layer, named `"default"`, holds
+    /// the defaults from `T::keymap_config()`.
+    pub fn derived() -> Self {
+        Self::new("default", T::keymap_config())
+    }
+}
+
+impl<T> LayeredConfig<T> {
+    /// Creates a `LayeredConfig` with `config` as its base layer.
+    pub fn new<S: Into<String>>(name: S, config: Config<T>) -> Self {
+        let origins = config
+            .items
+            .iter()
+            .map(|(_, item)| Origin {
+                layer: 0,
+                keys: vec![0; item.keys.len()],
+            })
+            .collect();
+
+        let mut layered = Self {
+            config,
+            layers: vec![name.into()],
+            origins,
+            matcher: Matcher::new(),
+        };
+        layered.rebuild_matcher();
+        layered
+    }
+
+    /// Applies a new layer on top of the existing ones.
+    ///
+    /// The layer is a map of `T = entry` pairs, read with the same rules as
+    /// [`DerivedConfig`](crate::DerivedConfig): entries replace, extend
+    /// (`add_keys`), trim (`remove_keys`) or disable (`false`) the item
+    /// from the previous layers, and new values are appended.
+    ///
+    /// On error, the config is left unchanged.
+    pub fn push<'de, S, D>(&mut self, name: S, deserializer: D) -> Result<(), D::Error>
+    where
+        S: Into<String>,
+        D: Deserializer<'de>,
+        T: Deserialize<'de> + PartialEq + Clone,
+    {
+        let entries = deserializer.deserialize_map(EntriesVisitor(PhantomData))?;
+        let layer = self.layers.len();
+        let mut items = self.config.items.clone();
+        let mut origins = self.origins.clone();
+
+        for (value, entry) in entries {
+            let pos = items.iter().position(|(existing, _)| existing == &value);
+            let previous = pos.map(|pos| (&items[pos].1, &origins[pos]));
+
+            let Some(item) = entry
+                .merge(previous.map(|(item, _)| item))
+                .map_err(de::Error::custom)?
+            else {
+                continue;
+            };
+
+            // Keys that were already bound keep their original layer
+            let keys = item
+                .keys
+                .iter()
+                .map(|key| {
+                    previous
+                        .and_then(|(prev, origin)| {
+                            let index = prev.keys.iter().position(|k| is_same_key(k, key))?;
+                            Some(origin.keys[index])
+                        })
+                        .unwrap_or(layer)
+                })
+                .collect();
+            let origin = Origin { layer, keys };
+
+            match pos {
+                Some(pos) => {
+                    items[pos].1 = item;
+                    origins[pos] = origin;
+                }
+                None => {
+                    items.push((value, item));
+                    origins.push(origin);
+                }
+            }
+        }
+
+        self.config = Config::try_new(items).map_err(de::Error::custom)?;
+        self.origins = origins;
+        self.layers.push(name.into());
+        self.rebuild_matcher();
+
+        Ok(())
+    }
+
+    /// Returns the layer names in the order they were applied.
+    pub fn layers(&self) -> &[String] {
+        &self.layers
+    }
+
+    /// Returns the name of the layer that last changed the item of `value`.
+    pub fn item_layer(&self, value: &T) -> Option<&str>
+    where
+        T: PartialEq,
+    {
+        let index = self.position(value)?;
+        Some(&self.layers[self.origins[index].layer])
+    }
+
+    /// Returns the name of the layer that bound `key` to `value`.
+    ///
+    /// `key` is compared by its parsed form, so `"del"` finds `"delete"`.
+    pub fn key_layer(&self, value: &T, key: &str) -> Option<&str>
+    where
+        T: PartialEq,
+    {
+        let index = self.position(value)?;
+        let (_, item) = &self.config.items[index];
+        let key_index = item.keys.iter().position(|k| is_same_key(k, key))?;
+
+        Some(&self.layers[self.origins[index].keys[key_index]])
+    }
+
+    /// Explains which binding matches the given key event, and where it comes
+    /// from.
+    pub fn explain<K: ToKeyMap>(&self, key: &K) -> Option<Provenance<'_, T>> {
+        self.explain_keymaps(&[key.to_keymap().ok()?])
+    }
+
+    /// Explains which binding matches the given key expression (e.g.
+    /// `"ctrl-s"` or `"g g"`), and where it comes from.
+    pub fn explain_key_str(&self, key: &str) -> Option<Provenance<'_, T>> {
+        self.explain_keymaps(&parse_seq(key).ok()?)
+    }
+
+    /// Explains which binding matches the given sequence of parsed
+    /// [`type@KeyMap`]s, and where it comes from.
+    ///
+    /// Like [`Config::get_item_by_keymaps`], this skips items with a
+    /// [`Item::when`] clause. Use [`LayeredConfig::explain_keymaps_with`] to
+    /// evaluate them.
+    pub fn explain_keymaps(&self, keys: &[KeyMap]) -> Option<Provenance<'_, T>> {
+        self.explain_by(keys, |item| item.when.is_none())
+    }
+
+    /// Explains which binding matches the given key event in `context`, and
+    /// where it comes from. See [`Config::get_with`].
+    pub fn explain_with<K: ToKeyMap, C: Context + ?Sized>(
+        &self,
+        key: &K,
+        context: &C,
+    ) -> Option<Provenance<'_, T>> {
+        self.explain_keymaps_with(&[key.to_keymap().ok()?], context)
+    }
+
+    /// Explains which binding matches the given sequence of parsed
+    /// [`type@KeyMap`]s in `context`, and where it comes from.
+    pub fn explain_keymaps_with<C: Context + ?Sized>(
+        &self,
+        keys: &[KeyMap],
+        context: &C,
+    ) -> Option<Provenance<'_, T>> {
+        self.explain_by(keys, |item| item.applies_in(context))
+    }
+
+    /// Explains the match of the last item bound to `keys` that passes
+    /// `filter`, falling back to the next matching pattern.
+    fn explain_by<F: Fn(&Item) -> bool>(
+        &self,
+        keys: &[KeyMap],
+        filter: F,
+    ) -> Option<Provenance<'_, T>> {
+        let entry = |entries: &[(usize, usize)]| {
+            entries
+                .iter()
+                .rev()
+                .find(|(index, _)| filter(&self.config.items[*index].1))
+                .copied()
+        };
+        let (index, key_index) = self
+            .matcher
+            .get_by(keys, |entries| entry(entries).is_some())
+            .and_then(|entries| entry(entries))?;
+        let (value, item) = &self.config.items[index];
+        let origin = &self.origins[index];
+
+        Some(Provenance {
+            value,
+            item,
+            key: &item.keys[key_index],
+            key_layer: &self.layers[origin.keys[key_index]],
+            item_layer: &self.layers[origin.layer],
+        })
+    }
+
+    /// Returns the merged [`Config<T>`], discarding the provenance.
+    pub fn into_config(self) -> Config<T> {
+        self.config
+    }
+
+    fn position(&self, value: &T) -> Option<usize>
+    where
+        T: PartialEq,
+    {
+        self.config.items.iter().position(|(v, _)| v == value)
+    }
+
+    fn rebuild_matcher(&mut self) {
+        let mut patterns: Vec<(Vec<KeyMap>, Entries)> = Vec::new();
+
+        for (index, (_, item)) in self.config.items.iter().enumerate() {
+            for (key_index, key) in item.keys.iter().enumerate() {
+                let Ok(keys) = parse_seq(key) else {
+                    continue;
+                };
+                match patterns.iter_mut().find(|(p, _)| *p == keys) {
+                    // A later key of the same item (e.g. "delete" after "del")
+                    Some((_, entries)) if entries.last().is_some_and(|(i, _)| *i == index) => {
+                        *entries.last_mut().unwrap() = (index, key_index);
+                    }
+                    Some((_, entries)) => entries.push((index, key_index)),
+                    None => patterns.push((keys, vec![(index, key_index)])),
+                }
+            }
+        }
+
+        self.matcher = patterns.into_iter().collect();
+    }
+}
+
+/// Reads the entries of a layer in order, without merging them.
+struct EntriesVisitor<T>(PhantomData<T>);
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for EntriesVisitor<T> {
+    type Value = Vec<(T, EntryRaw)>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a map of items (key = T, value = Item or bool)")
+    }
+
+    fn visit_map<M>(self, mut map: M) -> Result<Self::Value, M::Error>
+    where
+        M: MapAccess<'de>,
+    {
+        let mut entries = Vec::new();
+        while let Some(entry) = map.next_entry::<T, EntryRaw>()? {
+            entries.push(entry);
+        }
+
+        Ok(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> LayeredConfig<String> {
+        let defaults = Config::new(vec![
+            (
+                "Save".to_string(),
+                Item::new(vec!["ctrl-s".into()], "Save".into()),
+            ),
+            (
+                "Quit".to_string(),
+                Item::new(vec!["q".into(), "esc".into()], "Quit".into()),
+            ),
+            (
+                "Help".to_string(),
+                Item::new(vec!["?".into()], "Help".into()),
+            ),
+        ]);
+
+        LayeredConfig::new("default", defaults)
+    }
+
+    fn push(config: &mut LayeredConfig<String>, name: &str, data: &str) -> Result<(), String> {
+        config
+            .push(name, toml::Deserializer::parse(data).unwrap())
+            .map_err(|err| err.to_string())
+    }
+
+    #[test]
+    fn test_layers() {
+        let mut config = config();
+        push(
+            &mut config,
+            "system",
+            r#"Save = { keys = ["ctrl-s", "ctrl-x ctrl-s"] }"#,
+        )
+        .unwrap();
+        push(
+            &mut config,
+            "user",
+            r#"
+            Quit = { add_keys = ["ctrl-q"], remove_keys = ["esc"] }
+            Help = false
+        "#,
+        )
+        .unwrap();
+        push(&mut config, "project", r#"Build = { keys = ["f5"] }"#).unwrap();
+
+        assert_eq!(config.layers(), ["default", "system", "user", "project"]);
+
+        let p = config.explain_key_str("ctrl-x ctrl-s").unwrap();
+        assert_eq!(p.value, "Save");
+        assert_eq!(p.key, "ctrl-x ctrl-s");
+        assert_eq!(p.key_layer, "system");
+        assert_eq!(p.item_layer, "system");
+
+        // Re-listed keys keep the layer that first bound them
+        assert_eq!(config.key_layer(&"Save".into(), "ctrl-s"), Some("default"));
+        assert_eq!(config.key_layer(&"Quit".into(), "q"), Some("default"));
+        assert_eq!(config.key_layer(&"Quit".into(), "ctrl-q"), Some("user"));
+        assert_eq!(config.key_layer(&"Quit".into(), "esc"), None);
+        assert_eq!(config.get_item_by_key_str("esc"), None);
+
+        // Disabled items record the layer that disabled them
+        assert_eq!(config.item_layer(&"Help".into()), Some("user"));
+        assert_eq!(config.get_item_by_key_str("?"), None);
+
+        let p = config.explain_key_str("f5").unwrap();
+        assert_eq!((p.value.as_str(), p.item_layer), ("Build", "project"));
+    }
+
+    #[test]
+    fn test_push_error_keeps_config() {
+        let mut config = config();
+
+        let err = push(
+            &mut config,
+            "user",
+            r#"Save = { keys = ["ctrl-s", "ctrl-ss"] }"#,
+        )
+        .unwrap_err();
+        assert!(err.contains(r#"invalid key "ctrl-ss""#), "{err}");
+
+        let err = push(&mut config, "user", r#"Build = { remove_keys = ["f5"] }"#).unwrap_err();
+        assert!(err.contains("missing field `keys`"), "{err}");
+
+        assert_eq!(config.layers(), ["default"]);
+        assert_eq!(
+            config.explain_key_str("ctrl-s").unwrap().key_layer,
+            "default"
+        );
+    }
+
+    #[test]
+    fn test_when_override() {
+        let mut config = config();
+        push(
+            &mut config,
+            "user",
+            r#"Find = { keys = ["ctrl-s"], when = "editorFocus" }"#,
+        )
+        .unwrap();
+
+        // The gated override doesn't shadow the binding `Config` returns
+        let (value, _) = config.get_item_by_key_str("ctrl-s").unwrap();
+        let p = config.explain_key_str("ctrl-s").unwrap();
+        assert_eq!(value, "Save");
+        assert_eq!((p.value.as_str(), p.key_layer), ("Save", "default"));
+
+        // ...unless its clause holds
+        let keys = parse_seq("ctrl-s").unwrap();
+        let p = config
+            .explain_keymaps_with(&keys, &["editorFocus"][..])
+            .unwrap();
+        assert_eq!((p.value.as_str(), p.key_layer), ("Find", "user"));
+
+        let p = config.explain_keymaps_with(&keys, &["other"][..]).unwrap();
+        assert_eq!((p.value.as_str(), p.key_layer), ("Save", "default"));
+    }
+}
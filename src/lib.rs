@@ -1,16 +1,34 @@
 #![doc = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/README.md"))]
 
 // Re-exports
-pub use config::{Config, DerivedConfig, Item, KeyMapConfig};
+pub use analysis::{Binding, Diagnostic};
+pub use cheatsheet::{Cheatsheet, Format};
+pub use config::{
+    Config, ConfigError, DerivedConfig, InvalidKey, Item, ItemGroup, KeyMapConfig, KeySequence,
+    Overrides,
+};
 pub use keymap::{Error, FromKeyMap, IntoKeyMap, KeyGroupValue, KeyMap, ToKeyMap};
-pub use keymap_parser::{node, parser};
-pub use matcher::Matcher;
+pub use keymap_parser::{node, parser, style, when};
+pub use layered::{LayeredConfig, Provenance};
+pub use matcher::{Clock, Continuation, Matcher, Resolution, Resolver, SystemClock};
+pub use modal::{DerivedModalConfig, ModalConfig, ModalKeyMapConfig, ModeError};
+
+#[cfg(feature = "schema")]
+pub use schema::json_schema;
 
 #[cfg(feature = "derive")]
 #[doc(hidden)]
 pub use keymap_derive::KeyMap;
 
+mod analysis;
 pub mod backend;
+mod cheatsheet;
 pub mod config;
+pub mod export;
+pub mod import;
 mod keymap;
+mod layered;
 mod matcher;
+mod modal;
+#[cfg(feature = "schema")]
+mod schema;
@@ -17,6 +17,21 @@
 //!
 //! This ensures more specific patterns take precedence over broader ones.
 //!
+//! ## Key States
+//!
+//! A pattern with a key state (e.g. `release-space`) only matches input in
+//! that state. A pattern without one matches presses and repeats but not
+//! releases, and input without a state counts as a press (see
+//! [`State::accepts`]). A pattern in the exact state of the input takes
+//! precedence.
+//!
+//! | Pattern         | Input           | Match Result |
+//! | --------------- | --------------- | ------------ |
+//! | space           | press-space     | true         |
+//! | space           | release-space   | false        |
+//! | release-space   | release-space   | true         |
+//! | press-space     | space           | true         |
+//!
 //! ## Example Patterns
 //!
 //! | Pattern                  | Input          | Match Result |
@@ -30,10 +45,27 @@
 //!
 //! Each complete match path in the trie may store an associated value (e.g., action, ID, etc.).
 //!
+//! ## Incremental Resolution
+//!
+//! Input usually arrives one key at a time. A [`Resolver`] buffers keys and
+//! feeds them through the trie, reporting a [`Resolution`] for each key:
+//!
+//! | Patterns    | Input   | Resolutions                              |
+//! | ----------- | ------- | ---------------------------------------- |
+//! | g g         | g       | `Pending`                                |
+//! | g g         | g g     | `Pending`, `Matched(g g)`                |
+//! | g g         | g x     | `Pending`, `NoMatch` + `NoMatch`         |
+//! | g g, j      | g j     | `Pending`, `NoMatch` + `Matched(j)`      |
+//! | d, d d      | d d     | `Pending`, `Matched(d d)`                |
+//! | d, d d, j   | d j     | `Pending`, `Matched(d)` + `Matched(j)`   |
+//!
 //! See [`Matcher`] for the main interface and [`Trie`] for the underlying structure.
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
 
-use keymap_parser::node::{CharGroup, Key, Node};
+use keymap_parser::node::{CharGroup, Key, Node, State};
 
 #[derive(Debug)]
 struct Trie<T> {
@@ -51,6 +83,25 @@ impl<T> Trie<T> {
             groups: Vec::new(),
         }
     }
+
+    /// Returns `true` if this node or any node below it holds a value for
+    /// which `filter` returns `true`.
+    fn has_value(&self, filter: &dyn Fn(&T) -> bool) -> bool {
+        self.value.as_ref().is_some_and(filter)
+            || self.exact.values().any(|child| child.has_value(filter))
+            || self.groups.iter().any(|(_, child)| child.has_value(filter))
+    }
+
+    /// Iterates over the child nodes, exact keys first (sorted by their
+    /// string form for a stable order), followed by groups in insertion order.
+    fn children(&self) -> impl Iterator<Item = (&Node, &Trie<T>)> {
+        let mut exact = self.exact.iter().collect::<Vec<_>>();
+        exact.sort_by_cached_key(|(n, _)| n.to_string());
+
+        exact
+            .into_iter()
+            .chain(self.groups.iter().map(|(n, child)| (n, child)))
+    }
 }
 
 /// A pattern matcher that maps sequences of `Node`s to values.
@@ -107,7 +158,439 @@ impl<T> Matcher<T> {
 
     /// Attempts to retrieve a value for the given input node sequence.
     pub fn get(&self, nodes: &[Node]) -> Option<&T> {
-        search(&self.root, nodes, 0)
+        search(&self.root, nodes, 0, &|_| true)
+    }
+
+    /// Like [`Matcher::get`], but skips the values for which `filter` returns
+    /// `false` and falls back to the next matching pattern (e.g. a key group).
+    pub fn get_by<F: Fn(&T) -> bool>(&self, nodes: &[Node], filter: F) -> Option<&T> {
+        search(&self.root, nodes, 0, &filter)
+    }
+
+    /// Returns `true` if the given input node sequence is a proper prefix of
+    /// at least one pattern, i.e. more keys could still complete a match.
+    pub fn is_prefix(&self, nodes: &[Node]) -> bool {
+        has_continuation(&self.root, nodes, 0, &|_| true)
+    }
+
+    /// Like [`Matcher::is_prefix`], but only counts the patterns whose value
+    /// passes `filter`.
+    pub fn is_prefix_by<F: Fn(&T) -> bool>(&self, nodes: &[Node], filter: F) -> bool {
+        has_continuation(&self.root, nodes, 0, &filter)
+    }
+
+    /// Lists every key that may follow the given prefix.
+    ///
+    /// Each [`Continuation`] names the next key pattern (which may be a group
+    /// such as `@digit`), the value it would complete, and whether it leads to a
+    /// deeper prefix. The prefix may contain concrete keys (e.g. the keys
+    /// buffered by a [`Resolver`]) or the patterns themselves. An empty prefix
+    /// lists the first key of every pattern.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use keymap::Matcher;
+    /// use keymap_parser::parse_seq;
+    ///
+    /// let matcher = Matcher::from_iter([
+    ///     (parse_seq("ctrl-b n").unwrap(), "next"),
+    ///     (parse_seq("ctrl-b @digit").unwrap(), "select"),
+    /// ]);
+    ///
+    /// let next = matcher.continuations(&parse_seq("ctrl-b").unwrap());
+    /// assert_eq!(next[0].node.to_string(), "n");
+    /// assert_eq!(next[0].value, Some(&"next"));
+    /// assert_eq!(next[1].node.to_string(), "@digit");
+    /// ```
+    pub fn continuations(&self, prefix: &[Node]) -> Vec<Continuation<&T>> {
+        self.continuations_by(prefix, |_| true)
+    }
+
+    /// Like [`Matcher::continuations`], but only counts the patterns whose
+    /// value passes `filter`.
+    ///
+    /// Keys that only lead to filtered-out values are left out, and a key is
+    /// only a prefix if a value below it passes `filter`.
+    pub fn continuations_by<F: Fn(&T) -> bool>(
+        &self,
+        prefix: &[Node],
+        filter: F,
+    ) -> Vec<Continuation<&T>> {
+        let mut tries = Vec::new();
+        descend(&self.root, prefix, 0, &mut tries);
+
+        tries
+            .into_iter()
+            .flat_map(Trie::children)
+            .filter(|(_, child)| child.has_value(&filter))
+            .map(|(node, child)| Continuation {
+                node: node.clone(),
+                value: child.value.as_ref().filter(|value| filter(value)),
+                is_prefix: child.children().any(|(_, next)| next.has_value(&filter)),
+            })
+            .collect()
+    }
+}
+
+/// A key that may follow a prefix, as listed by [`Matcher::continuations`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Continuation<T> {
+    /// The next key pattern, which may be a key group such as `@digit`.
+    pub node: Node,
+    /// The value bound to the prefix followed by this key, if any.
+    pub value: Option<T>,
+    /// Whether more keys may follow this one, i.e. it leads to a deeper prefix.
+    pub is_prefix: bool,
+}
+
+impl<T> Continuation<T> {
+    /// Maps the completed value with the given function.
+    pub fn map<U, F: FnOnce(T) -> U>(self, f: F) -> Continuation<U> {
+        Continuation {
+            node: self.node,
+            value: self.value.map(f),
+            is_prefix: self.is_prefix,
+        }
+    }
+}
+
+/// The outcome of feeding a key into a [`Resolver`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Resolution<T> {
+    /// The buffered keys are a prefix of a longer pattern; more keys are needed.
+    Pending,
+    /// The buffered keys matched a pattern. Holds the associated value and the
+    /// input nodes that formed the match (e.g. to bind captured key groups).
+    Matched(T, Vec<Node>),
+    /// The buffered keys do not match any pattern and have been discarded.
+    NoMatch,
+}
+
+impl<T> Resolution<T> {
+    /// Maps the matched value with the given function, leaving other states untouched.
+    pub fn map<U, F: FnOnce(T) -> U>(self, f: F) -> Resolution<U> {
+        match self {
+            Resolution::Pending => Resolution::Pending,
+            Resolution::Matched(value, nodes) => Resolution::Matched(f(value), nodes),
+            Resolution::NoMatch => Resolution::NoMatch,
+        }
+    }
+}
+
+/// A source of the current time for a [`Resolver`].
+///
+/// The default [`SystemClock`] uses [`Instant::now`]. Tests can inject any
+/// `Fn() -> Instant` closure to control time without sleeping.
+pub trait Clock {
+    /// Returns the current instant.
+    fn now(&self) -> Instant;
+}
+
+/// A [`Clock`] backed by the system's monotonic clock.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+impl<F: Fn() -> Instant> Clock for F {
+    fn now(&self) -> Instant {
+        self()
+    }
+}
+
+/// A stateful resolver that matches key sequences one [`Node`] at a time.
+///
+/// The resolver buffers incoming keys until they either complete a pattern or
+/// can no longer match anything. When a complete pattern is also a prefix of a
+/// longer one (e.g. `d` and `d d`), the resolver keeps waiting. If the next key
+/// does not continue the longer pattern, the shorter match is emitted and the
+/// remaining keys are replayed from the start. Without a shorter match, the
+/// buffered keys resolve to [`Resolution::NoMatch`] and the key that broke the
+/// sequence is replayed on its own.
+///
+/// Because of this, a single call to [`Resolver::feed`] may produce more than
+/// one [`Resolution`]. The last one always describes the current buffer state.
+///
+/// # Timeout
+///
+/// With [`Resolver::with_timeout`], a pending sequence expires once no key has
+/// arrived for the given duration (similar to vim's `timeoutlen`). Call
+/// [`Resolver::poll`] from the event loop (e.g. when the input backend times
+/// out waiting for [`Resolver::deadline`]) to emit the shorter match, or
+/// [`Resolution::NoMatch`] if the buffered keys match nothing on their own.
+/// [`Resolver::flush`] does the same without waiting for the deadline.
+///
+/// # Key Releases
+///
+/// Backends that report key releases (e.g. crossterm with keyboard
+/// enhancement) send one for every key. A release that does not continue the
+/// buffered sequence is ignored, so it neither resets the buffer nor restarts
+/// the deadline. Bindings that ask for releases (e.g. `release-space`) still
+/// match.
+///
+/// # Example
+///
+/// ```
+/// use std::time::{Duration, Instant};
+/// use keymap::{Matcher, Resolution, Resolver};
+/// use keymap_parser::{parse, parse_seq};
+///
+/// let matcher = Matcher::from_iter([
+///     (parse_seq("d").unwrap(), "delete"),
+///     (parse_seq("d d").unwrap(), "delete line"),
+/// ]);
+///
+/// let mut resolver = Resolver::new().with_timeout(Duration::from_millis(500));
+/// let d = parse("d").unwrap();
+///
+/// assert_eq!(resolver.feed(&matcher, d.clone()), vec![Resolution::Pending]);
+/// assert_eq!(
+///     resolver.feed(&matcher, d.clone()),
+///     vec![Resolution::Matched(&"delete line", vec![d.clone(), d.clone()])]
+/// );
+///
+/// // No second `d` within the timeout: the shorter binding fires.
+/// resolver.feed(&matcher, d.clone());
+/// let later = Instant::now() + Duration::from_secs(1);
+/// assert_eq!(
+///     resolver.poll(&matcher, later),
+///     vec![Resolution::Matched(&"delete", vec![d])]
+/// );
+/// ```
+#[derive(Debug, Default)]
+pub struct Resolver<C = SystemClock> {
+    buffer: Vec<Node>,
+    /// Length of the longest buffered prefix that is itself a complete match.
+    fallback: Option<usize>,
+    timeout: Option<Duration>,
+    deadline: Option<Instant>,
+    clock: C,
+}
+
+impl Resolver {
+    /// Creates a new resolver with an empty buffer and no timeout.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<C: Clock> Resolver<C> {
+    /// Creates a new resolver that reads the current time from `clock`.
+    pub fn with_clock(clock: C) -> Self {
+        Self {
+            buffer: Vec::new(),
+            fallback: None,
+            timeout: None,
+            deadline: None,
+            clock,
+        }
+    }
+
+    /// Sets how long a pending sequence waits for the next key before it
+    /// expires. Without a timeout, a pending sequence waits indefinitely.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Feeds a single input node and returns the resulting resolutions.
+    ///
+    /// Returns an empty list if the node is an ignored key release (see
+    /// [Key Releases](Resolver#key-releases)).
+    pub fn feed<'a, T>(&mut self, matcher: &'a Matcher<T>, node: Node) -> Vec<Resolution<&'a T>> {
+        self.feed_by(matcher, node, |_| true)
+    }
+
+    /// Like [`Resolver::feed`], but ignores the patterns whose value does not
+    /// pass `filter`, as [`Matcher::get_by`] does. Such patterns neither
+    /// match nor keep a sequence pending.
+    pub fn feed_by<'a, T, F: Fn(&T) -> bool>(
+        &mut self,
+        matcher: &'a Matcher<T>,
+        node: Node,
+        filter: F,
+    ) -> Vec<Resolution<&'a T>> {
+        if node.state == Some(State::Released) && !self.continues(matcher, &node, &filter) {
+            return Vec::new();
+        }
+
+        let mut resolutions = Vec::new();
+        self.step(matcher, node, &filter, &mut resolutions);
+        self.touch(self.clock.now());
+
+        resolutions
+    }
+
+    /// Expires the pending sequence if its deadline has passed at `now`.
+    ///
+    /// Returns an empty list if nothing is pending, no timeout is set, or the
+    /// deadline has not been reached yet. If replayed keys leave a sequence
+    /// pending, its deadline restarts from `now`.
+    pub fn poll<'a, T>(&mut self, matcher: &'a Matcher<T>, now: Instant) -> Vec<Resolution<&'a T>> {
+        self.poll_by(matcher, now, |_| true)
+    }
+
+    /// Like [`Resolver::poll`], but with a `filter` as in [`Resolver::feed_by`].
+    pub fn poll_by<'a, T, F: Fn(&T) -> bool>(
+        &mut self,
+        matcher: &'a Matcher<T>,
+        now: Instant,
+        filter: F,
+    ) -> Vec<Resolution<&'a T>> {
+        match self.deadline {
+            Some(deadline) if now >= deadline => self.flush_at(matcher, &filter, now),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Resolves the pending sequence immediately, as if its timeout expired.
+    ///
+    /// Emits the longest buffered match (if any) and replays the remaining keys,
+    /// or [`Resolution::NoMatch`] if the buffered keys match nothing.
+    pub fn flush<'a, T>(&mut self, matcher: &'a Matcher<T>) -> Vec<Resolution<&'a T>> {
+        self.flush_by(matcher, |_| true)
+    }
+
+    /// Like [`Resolver::flush`], but with a `filter` as in [`Resolver::feed_by`].
+    pub fn flush_by<'a, T, F: Fn(&T) -> bool>(
+        &mut self,
+        matcher: &'a Matcher<T>,
+        filter: F,
+    ) -> Vec<Resolution<&'a T>> {
+        let now = self.clock.now();
+        self.flush_at(matcher, &filter, now)
+    }
+
+    /// Returns the instant at which the pending sequence expires, if any.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// Returns the keys buffered while waiting for a sequence to complete.
+    pub fn pending(&self) -> &[Node] {
+        &self.buffer
+    }
+
+    /// Returns `true` if the resolver is waiting for more keys.
+    pub fn is_pending(&self) -> bool {
+        !self.buffer.is_empty()
+    }
+
+    /// Discards any buffered keys.
+    pub fn reset(&mut self) {
+        self.buffer.clear();
+        self.fallback = None;
+        self.deadline = None;
+    }
+
+    /// Returns `true` if the buffered keys followed by `node` match or start a
+    /// pattern.
+    fn continues<T>(&self, matcher: &Matcher<T>, node: &Node, filter: &dyn Fn(&T) -> bool) -> bool {
+        let mut keys = self.buffer.clone();
+        keys.push(node.clone());
+
+        matcher.get_by(&keys, filter).is_some() || matcher.is_prefix_by(&keys, filter)
+    }
+
+    /// Resolves the pending sequence, restarting the deadline of any replayed
+    /// keys from `now`.
+    fn flush_at<'a, T>(
+        &mut self,
+        matcher: &'a Matcher<T>,
+        filter: &dyn Fn(&T) -> bool,
+        now: Instant,
+    ) -> Vec<Resolution<&'a T>> {
+        let mut resolutions = Vec::new();
+        if self.is_pending() {
+            self.expire(matcher, filter, &mut resolutions);
+            self.touch(now);
+        }
+
+        resolutions
+    }
+
+    /// Restarts the deadline from `now` while a sequence is pending, clearing
+    /// it otherwise.
+    fn touch(&mut self, now: Instant) {
+        self.deadline = match self.timeout {
+            Some(timeout) if self.is_pending() => Some(now + timeout),
+            _ => None,
+        };
+    }
+
+    fn step<'a, T>(
+        &mut self,
+        matcher: &'a Matcher<T>,
+        node: Node,
+        filter: &dyn Fn(&T) -> bool,
+        resolutions: &mut Vec<Resolution<&'a T>>,
+    ) {
+        // Only the final state of a replay is reported as pending.
+        if let Some(Resolution::Pending) = resolutions.last() {
+            resolutions.pop();
+        }
+
+        self.buffer.push(node);
+        let matched = matcher.get_by(&self.buffer, filter);
+
+        if matcher.is_prefix_by(&self.buffer, filter) {
+            if matched.is_some() {
+                self.fallback = Some(self.buffer.len());
+            }
+            resolutions.push(Resolution::Pending);
+            return;
+        }
+
+        if let Some(value) = matched {
+            self.fallback = None;
+            resolutions.push(Resolution::Matched(value, std::mem::take(&mut self.buffer)));
+            return;
+        }
+
+        // Without a shorter match, the key that broke the sequence starts over.
+        if self.fallback.is_none() && self.buffer.len() > 1 {
+            let node = self.buffer.pop().expect("buffer is not empty");
+            self.buffer.clear();
+            resolutions.push(Resolution::NoMatch);
+            self.step(matcher, node, filter, resolutions);
+            return;
+        }
+
+        self.expire(matcher, filter, resolutions);
+    }
+
+    /// Gives up on the buffered sequence, falling back to the longest complete
+    /// match and replaying the keys that followed it.
+    fn expire<'a, T>(
+        &mut self,
+        matcher: &'a Matcher<T>,
+        filter: &dyn Fn(&T) -> bool,
+        resolutions: &mut Vec<Resolution<&'a T>>,
+    ) {
+        match self.fallback.take() {
+            Some(len) => {
+                let rest = self.buffer.split_off(len);
+                let nodes = std::mem::take(&mut self.buffer);
+
+                if let Some(value) = matcher.get_by(&nodes, filter) {
+                    resolutions.push(Resolution::Matched(value, nodes));
+                }
+
+                // Replay the keys that followed the shorter match.
+                for node in rest {
+                    self.step(matcher, node, filter, resolutions);
+                }
+            }
+            None => {
+                self.buffer.clear();
+                resolutions.push(Resolution::NoMatch);
+            }
+        }
     }
 }
 
@@ -117,18 +600,41 @@ impl<T> Matcher<T> {
 /// 1. Exact match
 /// 2. Group match with same modifiers
 /// 3. Any-char group match with same modifiers
-fn search<'a, T>(node: &'a Trie<T>, nodes: &[Node], pos: usize) -> Option<&'a T> {
+///
+/// A keypad key that has no exact match is matched as the key of the main
+/// keyboard it stands for (e.g. `kp5` as `5`).
+fn search<'a, T>(
+    node: &'a Trie<T>,
+    nodes: &[Node],
+    pos: usize,
+    filter: &dyn Fn(&T) -> bool,
+) -> Option<&'a T> {
     if pos == nodes.len() {
-        return node.value.as_ref();
+        return node.value.as_ref().filter(|value| filter(value));
     }
 
     let input_node = &nodes[pos];
 
+    if let Some(main_node) = main_node(input_node) {
+        return exact(node, input_node)
+            .find_map(|child| search(child, nodes, pos + 1, filter))
+            .or_else(|| search_node(node, nodes, pos, &main_node, filter));
+    }
+
+    search_node(node, nodes, pos, input_node, filter)
+}
+
+/// Searches the children of `node` for `input_node`, the key at `pos`.
+fn search_node<'a, T>(
+    node: &'a Trie<T>,
+    nodes: &[Node],
+    pos: usize,
+    input_node: &Node,
+    filter: &dyn Fn(&T) -> bool,
+) -> Option<&'a T> {
     // 1. Exact match
-    if let Some(result) = node
-        .exact
-        .get(input_node)
-        .and_then(|child| search(child, nodes, pos + 1))
+    if let Some(result) =
+        exact(node, input_node).find_map(|child| search(child, nodes, pos + 1, filter))
     {
         return Some(result);
     }
@@ -136,8 +642,12 @@ fn search<'a, T>(node: &'a Trie<T>, nodes: &[Node], pos: usize) -> Option<&'a T>
     // 2. Group match
     if let Key::Char(ch) = input_node.key {
         if let Some(result) = node.groups.iter().find_map(|(n, child)| match n.key {
-            Key::Group(group) if n.modifiers == input_node.modifiers && group.matches(ch) => {
-                search(child, nodes, pos + 1)
+            Key::Group(group)
+                if n.modifiers == input_node.modifiers
+                    && group.matches(ch)
+                    && State::accepts(n.state, input_node.state) =>
+            {
+                search(child, nodes, pos + 1, filter)
             }
             _ => None,
         }) {
@@ -147,14 +657,110 @@ fn search<'a, T>(node: &'a Trie<T>, nodes: &[Node], pos: usize) -> Option<&'a T>
 
     // 3. Any-char group match
     node.groups.iter().find_map(|(n, child)| {
-        if matches!(n.key, Key::Group(CharGroup::Any)) {
-            search(child, nodes, pos + 1)
+        if matches!(n.key, Key::Group(CharGroup::Any)) && State::accepts(n.state, input_node.state)
+        {
+            search(child, nodes, pos + 1, filter)
         } else {
             None
         }
     })
 }
 
+/// Iterates over the children whose exact key accepts `input_node`: the key
+/// with the input's own state first, then the patterns without a state and
+/// finally, for input without a state, the `press-` patterns.
+fn exact<'a, T>(node: &'a Trie<T>, input_node: &Node) -> impl Iterator<Item = &'a Trie<T>> {
+    let mut states = vec![input_node.state];
+    for state in [None, Some(State::Pressed)] {
+        if !states.contains(&state) && State::accepts(state, input_node.state) {
+            states.push(state);
+        }
+    }
+
+    let input_node = input_node.clone();
+    states
+        .into_iter()
+        .filter_map(move |state| node.exact.get(&input_node.clone().with_state(state)))
+}
+
+/// Returns `input_node` with a keypad key replaced by the key of the main
+/// keyboard it stands for, or `None` if it is not a keypad key.
+fn main_node(input_node: &Node) -> Option<Node> {
+    let key = input_node.key.main_key()?;
+    Some(Node {
+        key,
+        ..input_node.clone()
+    })
+}
+
+/// Returns `true` if any trie path matching `nodes` can be extended further.
+///
+/// Unlike [`search`], this explores every matching branch, since a key may be
+/// a valid prefix through a group even when the exact branch is a dead end.
+fn has_continuation<T>(
+    node: &Trie<T>,
+    nodes: &[Node],
+    pos: usize,
+    filter: &dyn Fn(&T) -> bool,
+) -> bool {
+    let mut tries = Vec::new();
+    descend(node, nodes, pos, &mut tries);
+
+    tries
+        .iter()
+        .flat_map(|trie| trie.children())
+        .any(|(_, child)| child.has_value(filter))
+}
+
+/// Collects every trie node reachable by following `nodes` along all
+/// matching branches (exact and group).
+fn descend<'a, T>(node: &'a Trie<T>, nodes: &[Node], pos: usize, tries: &mut Vec<&'a Trie<T>>) {
+    if pos == nodes.len() {
+        tries.push(node);
+        return;
+    }
+
+    let input_node = &nodes[pos];
+    let main_node = main_node(input_node);
+
+    for child in exact(node, input_node) {
+        descend(child, nodes, pos + 1, tries);
+    }
+
+    if let Some(main_node) = &main_node {
+        for child in exact(node, main_node) {
+            descend(child, nodes, pos + 1, tries);
+        }
+    }
+
+    // Groups match keypad keys as the keys of the main keyboard.
+    let input_node = main_node.as_ref().unwrap_or(input_node);
+    for (n, child) in &node.groups {
+        if accepts(n, input_node) {
+            descend(child, nodes, pos + 1, tries);
+        }
+    }
+}
+
+/// Returns `true` if the group pattern `n` accepts `input_node`, either as the
+/// same group or through a group/any-char match as in [`search`].
+fn accepts(n: &Node, input_node: &Node) -> bool {
+    if n == input_node {
+        return true;
+    }
+    if !State::accepts(n.state, input_node.state) {
+        return false;
+    }
+
+    match (&n.key, &input_node.key) {
+        (Key::Group(CharGroup::Any), _) => true,
+        (Key::Group(group), Key::Char(ch)) => {
+            n.modifiers == input_node.modifiers && group.matches(*ch)
+        }
+        _ => false,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use keymap_parser::parse_seq;
@@ -203,6 +809,47 @@ mod tests {
         ]);
     }
 
+    #[test]
+    fn test_keypad() {
+        matches(&[
+            ("enter", "kpenter", true),
+            ("@digit", "kp5", true),
+            ("ctrl-+", "ctrl-kpplus", true),
+            ("kpminus", "-", false),
+        ]);
+
+        let matcher = Matcher::from_iter([
+            (parse_seq("5").unwrap(), "five"),
+            (parse_seq("kp5").unwrap(), "keypad five"),
+        ]);
+        let get = |input| matcher.get(&parse_seq(input).unwrap()).copied();
+
+        assert_eq!(get("5"), Some("five"));
+        assert_eq!(get("kp5"), Some("keypad five"));
+    }
+
+    #[test]
+    fn test_states() {
+        matches(&[
+            ("space", "repeat-space", true),
+            ("release-space", "space:up", true),
+            ("press-x", "x", true),
+            ("@digit:up", "release-5", true),
+            ("ctrl-@any:down", "repeat-ctrl-a", false),
+        ]);
+
+        let matcher = Matcher::from_iter([
+            (parse_seq("space").unwrap(), "jump"),
+            (parse_seq("press-space").unwrap(), "charge"),
+        ]);
+        let get = |input| matcher.get(&parse_seq(input).unwrap()).copied();
+
+        assert_eq!(get("space"), Some("jump"));
+        assert_eq!(get("press-space"), Some("charge"));
+        assert_eq!(get("repeat-space"), Some("jump"));
+        assert_eq!(get("release-space"), None);
+    }
+
     #[test]
     fn test_sequences() {
         matches(&[
@@ -210,4 +857,234 @@ mod tests {
             ("ctrl-@any shift-@upper", "ctrl-x shift-B", true),
         ]);
     }
+
+    fn resolve(patterns: &[&'static str], input: &str) -> Vec<Resolution<&'static str>> {
+        let matcher = Matcher::from_iter(patterns.iter().map(|p| (parse_seq(p).unwrap(), *p)));
+        let mut resolver = Resolver::new();
+
+        parse_seq(input)
+            .unwrap()
+            .into_iter()
+            .flat_map(|node| resolver.feed(&matcher, node))
+            .map(|r| r.map(|v| *v))
+            .collect()
+    }
+
+    fn matched(pattern: &'static str, input: &str) -> Resolution<&'static str> {
+        Resolution::Matched(pattern, parse_seq(input).unwrap())
+    }
+
+    #[test]
+    fn test_resolver_sequences() {
+        use Resolution::*;
+
+        assert_eq!(resolve(&["a"], "a"), vec![matched("a", "a")]);
+        assert_eq!(resolve(&["a"], "b"), vec![NoMatch]);
+        assert_eq!(
+            resolve(&["g g"], "g g"),
+            vec![Pending, matched("g g", "g g")]
+        );
+        assert_eq!(resolve(&["g g"], "g x"), vec![Pending, NoMatch, NoMatch]);
+        // The key that broke the sequence is not lost
+        assert_eq!(
+            resolve(&["g g", "j"], "g j"),
+            vec![Pending, NoMatch, matched("j", "j")]
+        );
+        assert_eq!(
+            resolve(&["ctrl-b @digit"], "ctrl-b 3"),
+            vec![Pending, matched("ctrl-b @digit", "ctrl-b 3")]
+        );
+    }
+
+    #[test]
+    fn test_resolver_ambiguous_prefix() {
+        use Resolution::*;
+
+        let patterns = &["d", "d d", "j", "g g"];
+
+        assert_eq!(
+            resolve(patterns, "d d"),
+            vec![Pending, matched("d d", "d d")]
+        );
+        assert_eq!(
+            resolve(patterns, "d j"),
+            vec![Pending, matched("d", "d"), matched("j", "j")]
+        );
+        assert_eq!(
+            resolve(patterns, "d x"),
+            vec![Pending, matched("d", "d"), NoMatch]
+        );
+        assert_eq!(
+            resolve(patterns, "d g"),
+            vec![Pending, matched("d", "d"), Pending]
+        );
+    }
+
+    #[test]
+    fn test_resolver_group_prefix() {
+        use Resolution::*;
+
+        // `j` matches `@any` on its own, but is also a prefix of `j j`.
+        let patterns = &["@any", "j j"];
+
+        assert_eq!(resolve(patterns, "k"), vec![matched("@any", "k")]);
+        assert_eq!(
+            resolve(patterns, "j j"),
+            vec![Pending, matched("j j", "j j")]
+        );
+        assert_eq!(
+            resolve(patterns, "j k"),
+            vec![Pending, matched("@any", "j"), matched("@any", "k")]
+        );
+    }
+
+    #[test]
+    fn test_resolver_releases() {
+        use Resolution::*;
+
+        let patterns = &["g g", "space", "release-space"];
+
+        assert_eq!(
+            resolve(patterns, "g release-g g"),
+            vec![Pending, matched("g g", "g g")]
+        );
+        assert_eq!(resolve(patterns, "release-x"), vec![]);
+        assert_eq!(
+            resolve(patterns, "space release-space"),
+            vec![
+                matched("space", "space"),
+                matched("release-space", "release-space")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_continuations() {
+        let matcher = Matcher::from_iter(
+            [
+                "ctrl-b n",
+                "ctrl-b c",
+                "ctrl-b @digit",
+                "ctrl-b g g",
+                "ctrl-b g",
+                "q",
+            ]
+            .map(|p| (parse_seq(p).unwrap(), p)),
+        );
+        let list = |prefix: &str| {
+            matcher
+                .continuations(&parse_seq(prefix).unwrap())
+                .into_iter()
+                .map(|c| (c.node.to_string(), c.value.copied(), c.is_prefix))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            list(""),
+            vec![
+                ("ctrl-b".to_string(), None, true),
+                ("q".to_string(), Some("q"), false),
+            ]
+        );
+        assert_eq!(
+            list("ctrl-b"),
+            vec![
+                ("c".to_string(), Some("ctrl-b c"), false),
+                ("g".to_string(), Some("ctrl-b g"), true),
+                ("n".to_string(), Some("ctrl-b n"), false),
+                ("@digit".to_string(), Some("ctrl-b @digit"), false),
+            ]
+        );
+        assert_eq!(
+            list("ctrl-b g"),
+            vec![("g".to_string(), Some("ctrl-b g g"), false)]
+        );
+        assert_eq!(list("ctrl-b 1"), vec![]);
+        assert_eq!(list("x"), vec![]);
+    }
+
+    #[test]
+    fn test_resolver_timeout() {
+        use std::{cell::Cell, rc::Rc};
+        use Resolution::*;
+
+        let start = Instant::now();
+        let now = Rc::new(Cell::new(start));
+        let clock = {
+            let now = now.clone();
+            move || now.get()
+        };
+
+        let matcher = Matcher::from_iter(["d", "d d", "g g h"].map(|p| (parse_seq(p).unwrap(), p)));
+        let mut resolver = Resolver::with_clock(clock).with_timeout(Duration::from_millis(100));
+        let key = |s: &str| parse_seq(s).unwrap().remove(0);
+
+        // Nothing is pending, so polling is a no-op.
+        assert_eq!(resolver.poll(&matcher, start), vec![]);
+        assert_eq!(resolver.deadline(), None);
+
+        // The deadline restarts with every pending key.
+        assert_eq!(resolver.feed(&matcher, key("d")), vec![Pending]);
+        assert_eq!(
+            resolver.deadline(),
+            Some(start + Duration::from_millis(100))
+        );
+        assert_eq!(
+            resolver.poll(&matcher, start + Duration::from_millis(99)),
+            vec![]
+        );
+
+        // Once expired, the shorter binding fires.
+        assert_eq!(
+            resolver.poll(&matcher, start + Duration::from_millis(100)),
+            vec![Matched(&"d", parse_seq("d").unwrap())]
+        );
+        assert!(!resolver.is_pending());
+        assert_eq!(resolver.deadline(), None);
+
+        // An incomplete sequence without a shorter binding is discarded.
+        now.set(start + Duration::from_millis(200));
+        assert_eq!(resolver.feed(&matcher, key("g")), vec![Pending]);
+        assert_eq!(resolver.feed(&matcher, key("g")), vec![Pending]);
+        assert_eq!(
+            resolver.deadline(),
+            Some(start + Duration::from_millis(300))
+        );
+        assert_eq!(
+            resolver.poll(&matcher, start + Duration::from_millis(300)),
+            vec![NoMatch]
+        );
+
+        // Flushing does not wait for the deadline.
+        resolver.feed(&matcher, key("d"));
+        assert_eq!(
+            resolver.flush(&matcher),
+            vec![Matched(&"d", parse_seq("d").unwrap())]
+        );
+        assert_eq!(resolver.flush(&matcher), vec![]);
+    }
+
+    #[test]
+    fn test_resolver_poll_replay_deadline() {
+        use Resolution::*;
+
+        let start = Instant::now();
+        let matcher = Matcher::from_iter(["d", "d d d"].map(|p| (parse_seq(p).unwrap(), p)));
+        let mut resolver =
+            Resolver::with_clock(move || start).with_timeout(Duration::from_millis(100));
+        let d = parse_seq("d").unwrap().remove(0);
+
+        resolver.feed(&matcher, d.clone());
+        resolver.feed(&matcher, d.clone());
+
+        // The replayed `d` is pending again, with a deadline based on the
+        // instant passed to `poll` rather than the clock.
+        let now = start + Duration::from_millis(500);
+        assert_eq!(
+            resolver.poll(&matcher, now),
+            vec![Matched(&"d", vec![d.clone()]), Pending]
+        );
+        assert_eq!(resolver.pending(), [d]);
+        assert_eq!(resolver.deadline(), Some(now + Duration::from_millis(100)));
+    }
 }
@@ -0,0 +1,945 @@
+//! Modal key mappings with a mode stack and inheritance.
+//!
+//! Applications such as editors bind the same keys to different actions
+//! depending on the current mode. [`ModalConfig`] holds one [`Config<T>`] per
+//! mode, where a mode may inherit the bindings of a parent mode (e.g.
+//! `visual` inherits `normal`), and keeps a stack of active modes.
+//!
+//! ```toml
+//! base = "normal"
+//!
+//! [normal]
+//! Quit = { keys = ["q"] }
+//! Visual = { keys = ["v"] }
+//!
+//! [visual]
+//! parent = "normal"
+//! Yank = { keys = ["y"] }
+//! Exit = { keys = ["esc"] }
+//! ```
+//!
+//! Lookups try the active (top) mode first and then walk up its parents, so
+//! `q` still quits in `visual` mode while `y` only yanks there. A mode with
+//! its own `Quit` item (e.g. with other or no keys) masks the `Quit` of its
+//! parents.
+//!
+//! [`DerivedModalConfig`] merges such tables into the default modes of a
+//! [`ModalKeyMapConfig`], the way [`DerivedConfig`](crate::DerivedConfig)
+//! does for a single config.
+use std::{
+    fmt,
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+};
+
+use keymap_parser::{parse_seq, when::Context};
+use serde::{
+    de::{self, IntoDeserializer, MapAccess, Visitor},
+    ser::SerializeMap,
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use crate::{
+    config::{merge_entry, EntryRaw},
+    keymap::ToKeyMap,
+    Config, Item, KeyMap, KeyMapConfig,
+};
+
+/// The reserved key of a mode table that names its parent mode.
+const PARENT: &str = "parent";
+
+/// The reserved top-level key that names the base mode.
+const BASE: &str = "base";
+
+/// A set of per-mode [`Config<T>`]s with a stack of active modes.
+///
+/// The base of the stack is the `base` entry of the configuration, or the
+/// mode given to [`ModalConfig::set_mode`]. Modes are entered with
+/// [`ModalConfig::push`] and left with [`ModalConfig::pop`].
+///
+/// # Example
+///
+/// ```
+/// # use keymap::ModalConfig;
+/// let mut modes: ModalConfig<String, String> = toml::from_str(r#"
+///     base = "normal"
+///
+///     [normal]
+///     Quit = { keys = ["q"] }
+///
+///     [visual]
+///     parent = "normal"
+///     Yank = { keys = ["y"] }
+/// "#).unwrap();
+///
+/// assert_eq!(modes.mode().unwrap(), "normal");
+/// assert_eq!(modes.get_item_by_key_str("y"), None);
+///
+/// modes.push(&"visual".to_string()).unwrap();
+/// assert_eq!(modes.get_item_by_key_str("y").unwrap().0, "Yank");
+/// assert_eq!(modes.get_item_by_key_str("q").unwrap().0, "Quit");
+///
+/// modes.pop();
+/// assert_eq!(modes.mode().unwrap(), "normal");
+/// ```
+#[derive(Debug)]
+pub struct ModalConfig<M, T> {
+    /// The modes in insertion order.
+    modes: Vec<Mode<M, T>>,
+
+    /// The active modes as indexes into `modes`. The last one is on top.
+    stack: Vec<usize>,
+}
+
+#[derive(Debug)]
+struct Mode<M, T> {
+    name: M,
+    parent: Option<usize>,
+    config: Config<T>,
+}
+
+/// An error returned when changing the modes of a [`ModalConfig`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ModeError<M> {
+    /// The mode does not exist.
+    UnknownMode(M),
+    /// The parent of `mode` does not exist.
+    UnknownParent { mode: M, parent: M },
+    /// Setting the parent would make `mode` inherit from itself.
+    InheritanceCycle(M),
+}
+
+impl<M: fmt::Debug> fmt::Display for ModeError<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ModeError::UnknownMode(mode) => write!(f, "unknown mode {mode:?}"),
+            ModeError::UnknownParent { mode, parent } => {
+                write!(f, "unknown parent {parent:?} of mode {mode:?}")
+            }
+            ModeError::InheritanceCycle(mode) => {
+                write!(f, "mode {mode:?} inherits from itself")
+            }
+        }
+    }
+}
+
+impl<M: fmt::Debug> std::error::Error for ModeError<M> {}
+
+/// A trait for providing the default modes of a
+/// [`DerivedModalConfig<M, T>`], the modal counterpart of [`KeyMapConfig`].
+///
+/// # Example
+///
+/// ```ignore
+/// impl ModalKeyMapConfig<String, Action> for Action {
+///     fn modal_keymap_config() -> ModalConfig<String, Action> {
+///         let item = |action: Action| (action.clone(), action.keymap_item());
+///
+///         let mut modes = ModalConfig::new();
+///         modes.insert("normal".into(), Config::new(vec![item(Action::Quit)]));
+///         modes.insert("insert".into(), Config::new(vec![item(Action::Exit)]));
+///         modes.set_parent(&"insert".into(), &"normal".into()).unwrap();
+///         modes
+///     }
+/// }
+/// ```
+pub trait ModalKeyMapConfig<M, T> {
+    /// Returns the default modes, with their parents and base mode.
+    fn modal_keymap_config() -> ModalConfig<M, T>;
+}
+
+/// A [`ModalConfig`] that merges user-provided mode tables with the default
+/// modes of [`ModalKeyMapConfig`].
+///
+/// Each mode table overrides, disables or extends the default items of the
+/// same mode, with the same rules as [`DerivedConfig`](crate::DerivedConfig):
+///
+/// ```toml
+/// [normal]
+/// # Keeps the default keys of `Quit` and adds "ctrl-c"
+/// Quit = { add_keys = ["ctrl-c"] }
+///
+/// [insert]
+/// # Disables the default binding
+/// Exit = false
+///
+/// [visual]
+/// # Disables the binding inherited from the parent mode
+/// Quit = false
+/// ```
+#[derive(Debug)]
+pub struct DerivedModalConfig<M, T>(ModalConfig<M, T>);
+
+impl<M, T> Deref for DerivedModalConfig<M, T> {
+    type Target = ModalConfig<M, T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<M, T> DerefMut for DerivedModalConfig<M, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<M, T> Default for ModalConfig<M, T> {
+    fn default() -> Self {
+        Self {
+            modes: Vec::new(),
+            stack: Vec::new(),
+        }
+    }
+}
+
+impl<M: PartialEq + Clone, T> ModalConfig<M, T> {
+    /// Creates an empty `ModalConfig`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a mode, replacing the config of an existing mode with the same
+    /// name. The first mode added becomes the active one.
+    pub fn insert(&mut self, mode: M, config: Config<T>) {
+        match self.position(&mode) {
+            Some(index) => self.modes[index].config = config,
+            None => {
+                self.modes.push(Mode {
+                    name: mode,
+                    parent: None,
+                    config,
+                });
+
+                if self.stack.is_empty() {
+                    self.stack.push(0);
+                }
+            }
+        }
+    }
+
+    /// Makes `mode` inherit the bindings of `parent`. Bindings of `mode` take
+    /// precedence over the inherited ones.
+    pub fn set_parent(&mut self, mode: &M, parent: &M) -> Result<(), ModeError<M>> {
+        let index = self.index(mode)?;
+        let parent_index = self
+            .position(parent)
+            .ok_or_else(|| ModeError::UnknownParent {
+                mode: mode.clone(),
+                parent: parent.clone(),
+            })?;
+
+        if self.chain(parent_index).any(|i| i == index) {
+            return Err(ModeError::InheritanceCycle(mode.clone()));
+        }
+
+        self.modes[index].parent = Some(parent_index);
+        Ok(())
+    }
+
+    /// Returns the parent of `mode`, if any.
+    pub fn parent(&self, mode: &M) -> Option<&M> {
+        let parent = self.modes[self.position(mode)?].parent?;
+        Some(&self.modes[parent].name)
+    }
+
+    /// Returns the config of `mode`, without inherited bindings.
+    pub fn config(&self, mode: &M) -> Option<&Config<T>> {
+        self.position(mode).map(|index| &self.modes[index].config)
+    }
+
+    /// Iterates over the mode names in insertion order.
+    pub fn modes(&self) -> impl Iterator<Item = &M> {
+        self.modes.iter().map(|mode| &mode.name)
+    }
+
+    /// Returns the active (top) mode.
+    pub fn mode(&self) -> Option<&M> {
+        self.stack.last().map(|&index| &self.modes[index].name)
+    }
+
+    /// Returns the mode stack, from the base to the active mode.
+    pub fn stack(&self) -> impl Iterator<Item = &M> {
+        self.stack.iter().map(|&index| &self.modes[index].name)
+    }
+
+    /// Enters `mode`, making it the active mode.
+    pub fn push(&mut self, mode: &M) -> Result<(), ModeError<M>> {
+        let index = self.index(mode)?;
+        self.stack.push(index);
+        Ok(())
+    }
+
+    /// Leaves the active mode and returns it. The base mode is never popped.
+    pub fn pop(&mut self) -> Option<&M> {
+        if self.stack.len() < 2 {
+            return None;
+        }
+
+        self.stack.pop().map(|index| &self.modes[index].name)
+    }
+
+    /// Clears the mode stack and makes `mode` its base.
+    pub fn set_mode(&mut self, mode: &M) -> Result<(), ModeError<M>> {
+        let index = self.index(mode)?;
+        self.stack = vec![index];
+        Ok(())
+    }
+
+    /// Applies the parents and the base mode read from a configuration.
+    fn apply(&mut self, parents: Vec<(M, M)>, base: Option<M>) -> Result<(), ModeError<M>> {
+        // Parents may be declared after their children
+        for (mode, parent) in parents {
+            self.set_parent(&mode, &parent)?;
+        }
+
+        match base {
+            Some(base) => self.set_mode(&base),
+            None => Ok(()),
+        }
+    }
+
+    fn position(&self, mode: &M) -> Option<usize> {
+        self.modes.iter().position(|m| &m.name == mode)
+    }
+
+    fn index(&self, mode: &M) -> Result<usize, ModeError<M>> {
+        self.position(mode)
+            .ok_or_else(|| ModeError::UnknownMode(mode.clone()))
+    }
+}
+
+impl<M, T: PartialEq> ModalConfig<M, T> {
+    /// Retrieve the key type `T` bound to the given key event in the active
+    /// mode or one of its parents.
+    ///
+    /// A key type `T` with an item in a mode masks the bindings of the same
+    /// `T` in its parents, so a mode can rebind or disable (with empty keys)
+    /// an inherited binding.
+    pub fn get<K: ToKeyMap>(&self, key: &K) -> Option<&T> {
+        self.get_item(key).map(|(t, _)| t)
+    }
+
+    /// Retrieve the `(T, Item)` pair bound to the given key event in the
+    /// active mode or one of its parents.
+    pub fn get_item<K: ToKeyMap>(&self, key: &K) -> Option<(&T, &Item)> {
+        self.get_item_by_keymaps(&[key.to_keymap().ok()?])
+    }
+
+    /// Retrieve the dynamically bound key type `T` (see [`Config::get_bound`])
+    /// in the active mode or one of its parents.
+    pub fn get_bound<K: ToKeyMap>(&self, key: &K) -> Option<T>
+    where
+        T: KeyMapConfig<T> + Clone,
+    {
+        let keys = [key.to_keymap().ok()?];
+        self.get_item_by_keymaps(&keys).map(|(t, _)| t.bind(&keys))
+    }
+
+    /// Lookup an `(T, Item)` pair by a sequence of parsed [`type@KeyMap`]s in
+    /// the active mode, then in each of its parents.
+    ///
+    /// Like [`Config::get_item_by_keymaps`], this skips items with a
+    /// [`Item::when`] clause. Use [`ModalConfig::get_item_by_keymaps_with`]
+    /// to evaluate them.
+    pub fn get_item_by_keymaps(&self, keys: &[KeyMap]) -> Option<(&T, &Item)> {
+        self.find_in_modes(|config, masked| config.get_item_by_keymaps_by(keys, masked))
+    }
+
+    /// Lookup an `(T, Item)` pair by a raw key expression (e.g. `"g g"`) in
+    /// the active mode, then in each of its parents.
+    pub fn get_item_by_key_str(&self, key: &str) -> Option<(&T, &Item)> {
+        self.get_item_by_keymaps(&parse_seq(key).ok()?)
+    }
+
+    /// Retrieve the key type `T` bound to the given key event in the active
+    /// mode or one of its parents, skipping items whose [`Item::when`] clause
+    /// does not hold in `context`. See [`Config::get_with`].
+    pub fn get_with<K: ToKeyMap, C: Context + ?Sized>(&self, key: &K, context: &C) -> Option<&T> {
+        self.get_item_with(key, context).map(|(t, _)| t)
+    }
+
+    /// Retrieve the `(T, Item)` pair bound to the given key event in the
+    /// active mode or one of its parents in `context`. See
+    /// [`ModalConfig::get_with`].
+    pub fn get_item_with<K: ToKeyMap, C: Context + ?Sized>(
+        &self,
+        key: &K,
+        context: &C,
+    ) -> Option<(&T, &Item)> {
+        self.get_item_by_keymaps_with(&[key.to_keymap().ok()?], context)
+    }
+
+    /// Lookup an `(T, Item)` pair by a sequence of parsed [`type@KeyMap`]s in
+    /// the active mode, then in each of its parents, in `context`. See
+    /// [`ModalConfig::get_with`].
+    pub fn get_item_by_keymaps_with<C: Context + ?Sized>(
+        &self,
+        keys: &[KeyMap],
+        context: &C,
+    ) -> Option<(&T, &Item)> {
+        self.find_in_modes(|config, masked| {
+            config.get_item_by_keymaps_with_by(keys, context, masked)
+        })
+    }
+
+    /// Runs `lookup` on the config of the active mode, then on each of its
+    /// parents, until it finds an item.
+    ///
+    /// `lookup` is given a filter that skips the key types a more specific
+    /// mode has an item for.
+    fn find_in_modes<'a>(
+        &'a self,
+        lookup: impl Fn(&'a Config<T>, &dyn Fn(&T) -> bool) -> Option<(&'a T, &'a Item)>,
+    ) -> Option<(&'a T, &'a Item)> {
+        let configs = self.active_configs().collect::<Vec<_>>();
+
+        configs.iter().enumerate().find_map(|(i, config)| {
+            lookup(config, &|t| {
+                !configs[..i]
+                    .iter()
+                    .any(|config| config.items.iter().any(|(other, _)| other == t))
+            })
+        })
+    }
+}
+
+impl<M, T> ModalConfig<M, T> {
+    /// Iterates over the configs of the active mode and its parents, from the
+    /// most to the least specific.
+    pub fn active_configs(&self) -> impl Iterator<Item = &Config<T>> {
+        self.stack
+            .last()
+            .into_iter()
+            .flat_map(|&index| self.chain(index))
+            .map(|index| &self.modes[index].config)
+    }
+
+    /// Iterates over `index` and the indexes of its parents.
+    fn chain(&self, index: usize) -> impl Iterator<Item = usize> + '_ {
+        std::iter::successors(Some(index), |&i| self.modes[i].parent)
+    }
+}
+
+impl<M: Serialize, T: Serialize> Serialize for ModalConfig<M, T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(self.modes.len() + 1))?;
+        if let Some(&base) = self.stack.first() {
+            map.serialize_entry(BASE, &self.modes[base].name)?;
+        }
+        for mode in &self.modes {
+            let table = ModeTable {
+                parent: mode.parent.map(|index| &self.modes[index].name),
+                config: &mode.config,
+            };
+            map.serialize_entry(&mode.name, &table)?;
+        }
+        map.end()
+    }
+}
+
+impl<M: Serialize, T: Serialize> Serialize for DerivedModalConfig<M, T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.0.serialize(serializer)
+    }
+}
+
+/// A mode table for serialization: the `parent` entry followed by the items.
+struct ModeTable<'a, M, T> {
+    parent: Option<&'a M>,
+    config: &'a Config<T>,
+}
+
+impl<M: Serialize, T: Serialize> Serialize for ModeTable<'_, M, T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let items = &self.config.items;
+        let mut map = serializer.serialize_map(Some(items.len() + 1))?;
+        if let Some(parent) = self.parent {
+            map.serialize_entry(PARENT, parent)?;
+        }
+        for (t, item) in items {
+            map.serialize_entry(t, item)?;
+        }
+        map.end()
+    }
+}
+
+/// Custom deserialization for [`ModalConfig<M, T>`] from a map of mode
+/// tables. Each table holds `T = Item` entries like [`Config<T>`], plus an
+/// optional `parent` entry naming the mode it inherits from. The optional
+/// top-level `base` entry names the base mode of the stack:
+///
+/// ```toml
+/// base = "normal"
+///
+/// [normal]
+/// Quit = { keys = ["q"] }
+///
+/// [insert]
+/// parent = "normal"
+/// Exit = { keys = ["esc"] }
+/// ```
+///
+/// Keys of `T` and mode names are read from strings, so `parent` cannot be
+/// used as a key and `base` cannot be used as a mode.
+///
+/// Without a `base` entry no mode is active until [`ModalConfig::set_mode`]
+/// is called, since the order of the tables depends on the format (e.g.
+/// `toml` sorts tables by name).
+impl<'de, M, T> Deserialize<'de> for ModalConfig<M, T>
+where
+    M: Deserialize<'de> + PartialEq + Clone + fmt::Debug,
+    T: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let raw = ModesRaw::<M, T, Item>::deserialize(deserializer)?;
+        let mut modes = ModalConfig::new();
+        let mut parents = Vec::new();
+
+        for (mode, table) in raw.modes {
+            if let Some(parent) = table.parent {
+                parents.push((mode.clone(), parent));
+            }
+            let config = Config::try_new(table.items).map_err(de::Error::custom)?;
+            modes.insert(mode, config);
+        }
+
+        modes.stack.clear();
+        modes.apply(parents, raw.base).map_err(de::Error::custom)?;
+
+        Ok(modes)
+    }
+}
+
+/// Custom deserialization for [`DerivedModalConfig<M, T>`], which starts from
+/// the default modes of `T::modal_keymap_config()` and merges each mode table
+/// into the defaults of the same mode, as
+/// [`DerivedConfig`](crate::DerivedConfig) does for a single config. Tables
+/// of other modes add new modes.
+///
+/// The `parent` and `base` entries override the defaults. Without a `base`
+/// entry, the default base mode stays active.
+impl<'de, M, T> Deserialize<'de> for DerivedModalConfig<M, T>
+where
+    M: Deserialize<'de> + PartialEq + Clone + fmt::Debug,
+    T: Deserialize<'de> + ModalKeyMapConfig<M, T> + PartialEq,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let raw = ModesRaw::<M, T, EntryRaw>::deserialize(deserializer)?;
+        let mut modes = T::modal_keymap_config();
+        let stack = modes.stack.clone();
+        let mut parents = Vec::new();
+
+        for (mode, table) in raw.modes {
+            if let Some(parent) = table.parent {
+                parents.push((mode.clone(), parent));
+            }
+
+            // Start with the default items of the mode
+            let mut items = match modes.position(&mode) {
+                Some(index) => std::mem::take(&mut modes.modes[index].config.items),
+                None => Vec::new(),
+            };
+            for (t, entry) in table.items {
+                // Disabling an item that the mode does not have keeps a
+                // disabled item, which masks the inherited one
+                if matches!(entry, EntryRaw::Enabled(false)) && !items.iter().any(|(u, _)| u == &t)
+                {
+                    items.push((t, Item::new(Vec::new(), String::new())));
+                    continue;
+                }
+                merge_entry(&mut items, t, entry).map_err(de::Error::custom)?;
+            }
+
+            let config = Config::try_new(items).map_err(de::Error::custom)?;
+            modes.insert(mode, config);
+        }
+
+        // New modes don't change the default stack
+        modes.stack = stack;
+        modes.apply(parents, raw.base).map_err(de::Error::custom)?;
+
+        Ok(DerivedModalConfig(modes))
+    }
+}
+
+/// The top-level entries of a modal config: the optional `base` mode and the
+/// mode tables, whose items are read as `V`.
+struct ModesRaw<M, T, V> {
+    base: Option<M>,
+    modes: Vec<(M, ModeRaw<M, T, V>)>,
+}
+
+impl<'de, M, T, V> Deserialize<'de> for ModesRaw<M, T, V>
+where
+    M: Deserialize<'de>,
+    T: Deserialize<'de>,
+    V: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct ModalVisitor<M, T, V>(PhantomData<(M, T, V)>);
+
+        impl<'de, M, T, V> Visitor<'de> for ModalVisitor<M, T, V>
+        where
+            M: Deserialize<'de>,
+            T: Deserialize<'de>,
+            V: Deserialize<'de>,
+        {
+            type Value = ModesRaw<M, T, V>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a map of modes (key = M, value = map of items)")
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                let mut base = None;
+                let mut modes = Vec::new();
+
+                while let Some(key) = map.next_key::<String>()? {
+                    if key == BASE {
+                        base = Some(map.next_value::<M>()?);
+                    } else {
+                        let mode = M::deserialize(key.into_deserializer())?;
+                        modes.push((mode, map.next_value::<ModeRaw<M, T, V>>()?));
+                    }
+                }
+
+                Ok(ModesRaw { base, modes })
+            }
+        }
+
+        deserializer.deserialize_map(ModalVisitor(PhantomData))
+    }
+}
+
+/// A mode table as read from the configuration.
+struct ModeRaw<M, T, V> {
+    parent: Option<M>,
+    items: Vec<(T, V)>,
+}
+
+impl<'de, M, T, V> Deserialize<'de> for ModeRaw<M, T, V>
+where
+    M: Deserialize<'de>,
+    T: Deserialize<'de>,
+    V: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct ModeVisitor<M, T, V>(PhantomData<(M, T, V)>);
+
+        impl<'de, M, T, V> Visitor<'de> for ModeVisitor<M, T, V>
+        where
+            M: Deserialize<'de>,
+            T: Deserialize<'de>,
+            V: Deserialize<'de>,
+        {
+            type Value = ModeRaw<M, T, V>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a map of items with an optional parent")
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                let mut parent = None;
+                let mut items = Vec::new();
+
+                while let Some(key) = map.next_key::<String>()? {
+                    if key == PARENT {
+                        parent = Some(map.next_value::<M>()?);
+                    } else {
+                        let t = T::deserialize(key.into_deserializer())?;
+                        items.push((t, map.next_value::<V>()?));
+                    }
+                }
+
+                Ok(ModeRaw { parent, items })
+            }
+        }
+
+        deserializer.deserialize_map(ModeVisitor(PhantomData))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+        [visual]
+        parent = "normal"
+        Yank = { keys = ["y"] }
+        Exit = { keys = ["esc", "q"] }
+
+        [normal]
+        Quit = { keys = ["q"] }
+        Visual = { keys = ["v"] }
+        Help = { keys = ["?"] }
+
+        [block]
+        parent = "visual"
+        Insert = { keys = ["shift-i"] }
+    "#;
+
+    fn modes() -> ModalConfig<String, String> {
+        toml::from_str(CONFIG).unwrap()
+    }
+
+    fn get(modes: &ModalConfig<String, String>, key: &str) -> Option<String> {
+        modes.get_item_by_key_str(key).map(|(t, _)| t.clone())
+    }
+
+    #[test]
+    fn test_inheritance() {
+        let mut modes = modes();
+        modes.set_mode(&"normal".into()).unwrap();
+
+        assert_eq!(get(&modes, "q").as_deref(), Some("Quit"));
+        assert_eq!(get(&modes, "y"), None);
+
+        modes.push(&"block".into()).unwrap();
+        assert_eq!(get(&modes, "shift-i").as_deref(), Some("Insert"));
+        // The closest mode wins
+        assert_eq!(get(&modes, "q").as_deref(), Some("Exit"));
+        assert_eq!(get(&modes, "?").as_deref(), Some("Help"));
+        assert_eq!(modes.parent(&"block".into()).unwrap(), "visual");
+    }
+
+    #[test]
+    fn test_stack() {
+        let mut modes = modes();
+        modes.set_mode(&"normal".into()).unwrap();
+        modes.push(&"visual".into()).unwrap();
+        modes.push(&"block".into()).unwrap();
+        assert_eq!(
+            modes.stack().collect::<Vec<_>>(),
+            vec!["normal", "visual", "block"]
+        );
+
+        assert_eq!(modes.pop().unwrap(), "block");
+        assert_eq!(modes.pop().unwrap(), "visual");
+        assert_eq!(modes.pop(), None);
+        assert_eq!(modes.mode().unwrap(), "normal");
+
+        assert_eq!(
+            modes.push(&"insert".into()),
+            Err(ModeError::UnknownMode("insert".into()))
+        );
+    }
+
+    #[test]
+    fn test_parent_errors() {
+        let mut modes = modes();
+        assert_eq!(
+            modes.set_parent(&"normal".into(), &"block".into()),
+            Err(ModeError::InheritanceCycle("normal".into()))
+        );
+
+        let err = toml::from_str::<ModalConfig<String, String>>(
+            r#"
+            [visual]
+            parent = "normal"
+        "#,
+        )
+        .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains(r#"unknown parent "normal" of mode "visual""#),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_serialize_round_trip() {
+        let modes = modes();
+        let data = toml::to_string(&modes).unwrap();
+        let reloaded: ModalConfig<String, String> = toml::from_str(&data).unwrap();
+
+        assert_eq!(
+            reloaded.modes().collect::<Vec<_>>(),
+            modes.modes().collect::<Vec<_>>()
+        );
+        assert_eq!(reloaded.parent(&"block".into()).unwrap(), "visual");
+        assert_eq!(
+            reloaded.config(&"normal".into()).unwrap().items,
+            modes.config(&"normal".into()).unwrap().items
+        );
+    }
+
+    #[test]
+    fn test_base() {
+        assert_eq!(modes().mode(), None);
+
+        let config = format!("base = \"visual\"\n{CONFIG}");
+        let modes: ModalConfig<String, String> = toml::from_str(&config).unwrap();
+        assert_eq!(modes.mode().unwrap(), "visual");
+        assert_eq!(get(&modes, "q").as_deref(), Some("Exit"));
+
+        // The base mode is written first and read back
+        let reloaded: ModalConfig<String, String> =
+            toml::from_str(&toml::to_string(&modes).unwrap()).unwrap();
+        assert_eq!(reloaded.mode().unwrap(), "visual");
+
+        let err = toml::from_str::<ModalConfig<String, String>>(
+            r#"
+            base = "insert"
+            [normal]
+        "#,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("unknown mode"), "{err}");
+    }
+
+    impl ModalKeyMapConfig<String, String> for String {
+        fn modal_keymap_config() -> ModalConfig<String, String> {
+            let item = |key: &str| Item::new(vec![key.into()], String::new());
+
+            let mut modes = ModalConfig::new();
+            modes.insert(
+                "normal".into(),
+                Config::new(vec![("Quit".into(), item("q")), ("Help".into(), item("?"))]),
+            );
+            modes.insert(
+                "visual".into(),
+                Config::new(vec![("Yank".into(), item("y"))]),
+            );
+            modes
+                .set_parent(&"visual".into(), &"normal".into())
+                .unwrap();
+            modes
+        }
+    }
+
+    #[test]
+    fn test_derived_modes() {
+        let mut modes: DerivedModalConfig<String, String> = toml::from_str(
+            r#"
+            [visual]
+            Yank = { add_keys = ["Y"] }
+
+            [normal]
+            Quit = { add_keys = ["ctrl-c"] }
+            Help = false
+
+            [insert]
+            parent = "normal"
+            Exit = { keys = ["esc"] }
+        "#,
+        )
+        .unwrap();
+
+        // The default base mode is kept
+        assert_eq!(modes.mode().unwrap(), "normal");
+        assert_eq!(get(&modes, "q").as_deref(), Some("Quit"));
+        assert_eq!(get(&modes, "ctrl-c").as_deref(), Some("Quit"));
+        assert_eq!(get(&modes, "?"), None);
+
+        // Default parents are kept and new modes are added
+        modes.push(&"visual".into()).unwrap();
+        assert_eq!(get(&modes, "y").as_deref(), Some("Yank"));
+        assert_eq!(get(&modes, "Y").as_deref(), Some("Yank"));
+        assert_eq!(get(&modes, "ctrl-c").as_deref(), Some("Quit"));
+        assert_eq!(modes.parent(&"insert".into()).unwrap(), "normal");
+
+        let modes: DerivedModalConfig<String, String> =
+            toml::from_str("base = \"visual\"").unwrap();
+        assert_eq!(modes.mode().unwrap(), "visual");
+        assert_eq!(get(&modes, "?").as_deref(), Some("Help"));
+    }
+
+    #[test]
+    fn test_get_with_context() {
+        let mut modes: ModalConfig<String, String> = toml::from_str(
+            r#"
+            base = "normal"
+
+            [normal]
+            Quit = { keys = ["q"] }
+            Save = { keys = ["ctrl-s"], when = "dirty" }
+
+            [visual]
+            parent = "normal"
+            Exit = { keys = ["q"], when = "selection" }
+        "#,
+        )
+        .unwrap();
+        modes.push(&"visual".into()).unwrap();
+
+        let get_with = |key: &str, context: &[&str]| {
+            let key = parse_seq(key).unwrap();
+            modes
+                .get_item_by_keymaps_with(&key, context)
+                .map(|(t, _)| t.as_str())
+        };
+
+        // Gated items only fire in a context where their clause holds
+        assert_eq!(get(&modes, "ctrl-s"), None);
+        assert_eq!(get_with("ctrl-s", &["dirty"]), Some("Save"));
+        assert_eq!(get_with("q", &["selection"]), Some("Exit"));
+        assert_eq!(get_with("q", &[]), Some("Quit"));
+        assert_eq!(get(&modes, "q").as_deref(), Some("Quit"));
+    }
+
+    #[test]
+    fn test_masked_parent() {
+        let mut modes: DerivedModalConfig<String, String> = toml::from_str(
+            r#"
+            [visual]
+            Quit = false
+            Help = { keys = ["h"] }
+        "#,
+        )
+        .unwrap();
+
+        assert_eq!(get(&modes, "q").as_deref(), Some("Quit"));
+        assert_eq!(get(&modes, "?").as_deref(), Some("Help"));
+
+        modes.push(&"visual".into()).unwrap();
+        assert_eq!(get(&modes, "q"), None);
+        assert_eq!(get(&modes, "?"), None);
+        assert_eq!(get(&modes, "h").as_deref(), Some("Help"));
+
+        // An item without keys masks the parent in a plain config too
+        let mut modes: ModalConfig<String, String> = toml::from_str(
+            r#"
+            base = "normal"
+
+            [normal]
+            Quit = { keys = ["q"] }
+
+            [visual]
+            parent = "normal"
+            Quit = { keys = [] }
+        "#,
+        )
+        .unwrap();
+        modes.push(&"visual".into()).unwrap();
+        assert_eq!(get(&modes, "q"), None);
+    }
+}
@@ -0,0 +1,237 @@
+//! JSON Schema generation for keymap config files.
+//!
+//! Editors such as VS Code and taplo use a JSON Schema to autocomplete and
+//! validate config files. The generated schema lists the known actions as
+//! properties, describes the fields of an item, and validates key strings
+//! against the syntax of [`keymap_parser`].
+//!
+//! ```toml
+//! #:schema ./keymap.schema.json
+//! Quit = { keys = ["q", "esc"], description = "Quit" }
+//! ```
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+use crate::{config::Field, parser, Config, Item, KeyMapConfig};
+
+const DRAFT: &str = "http://json-schema.org/draft-07/schema#";
+
+impl<T: Serialize> Config<T> {
+    /// Returns a JSON Schema for [`Config<T>`] files with the items of this
+    /// config as the allowed actions.
+    ///
+    /// Every item must list its `keys` (or `add_keys`). For files read as a
+    /// [`DerivedConfig<T>`](crate::DerivedConfig), use [`json_schema`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an action cannot be serialized as a property name.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use keymap::Config;
+    /// let config: Config<String> = toml::from_str(r#"
+    ///     Quit = { keys = ["q"], description = "Quit" }
+    /// "#).unwrap();
+    ///
+    /// let schema = config.json_schema().unwrap();
+    /// assert!(schema["properties"]["Quit"].is_object());
+    /// ```
+    pub fn json_schema(&self) -> Result<Value, serde_json::Error> {
+        let item = json!({
+            "allOf": [
+                { "$ref": "#/definitions/item" },
+                { "anyOf": [{ "required": ["keys"] }, { "required": ["add_keys"] }] },
+            ],
+        });
+
+        schema(self, item)
+    }
+}
+
+/// Returns a JSON Schema for [`DerivedConfig<T>`](crate::DerivedConfig)
+/// files, e.g. for a `#[derive(KeyMap)]` enum.
+///
+/// Each variant is an optional property holding either a boolean that keeps
+/// (`true`) or disables (`false`) the default binding, or a (partial) item.
+///
+/// # Errors
+///
+/// Returns an error if a variant cannot be serialized as a property name.
+///
+/// # Example
+///
+/// ```ignore
+/// let schema = keymap::json_schema::<Action>()?;
+///
+/// std::fs::write("keymap.schema.json", serde_json::to_string_pretty(&schema)?)?;
+/// ```
+pub fn json_schema<T: KeyMapConfig<T> + Serialize>() -> Result<Value, serde_json::Error> {
+    let entry = json!({
+        "oneOf": [
+            { "type": "boolean" },
+            { "$ref": "#/definitions/item" },
+        ],
+    });
+
+    schema(&T::keymap_config(), entry)
+}
+
+/// Builds the schema of a map from the actions of `config` to `entry`.
+fn schema<T: Serialize>(config: &Config<T>, entry: Value) -> Result<Value, serde_json::Error> {
+    let properties = config
+        .items
+        .iter()
+        .map(|(t, item)| Ok((name(t)?, property(item, &entry))))
+        .collect::<Result<Map<_, _>, serde_json::Error>>()?;
+
+    Ok(json!({
+        "$schema": DRAFT,
+        "type": "object",
+        "properties": properties,
+        "additionalProperties": false,
+        "definitions": {
+            "key": {
+                "type": "string",
+                "description": "A key sequence, e.g. \"ctrl-b n\"",
+                "pattern": parser::pattern(),
+            },
+            "keys": {
+                "type": "array",
+                "items": { "$ref": "#/definitions/key" },
+            },
+            "item": item(),
+        },
+    }))
+}
+
+/// The schema of an item, matching the fields accepted by `ItemRaw`.
+fn item() -> Value {
+    let keys = json!({ "$ref": "#/definitions/keys" });
+    let string = |description: &str| json!({ "type": "string", "description": description });
+
+    json!({
+        "type": "object",
+        "properties": {
+            "keys": keys,
+            "description": string("A description of the binding"),
+            "symbol": string("The symbol shown in place of the first key"),
+            "help": string("A short help text"),
+            "when": string("The condition under which the binding applies, e.g. \"editorFocus && !readOnly\""),
+            "group": string("The category of the binding on help screens"),
+            "category": string("An alias of `group`"),
+            "order": { "type": "integer", "description": "A sorting hint for help screens" },
+            "add_keys": keys,
+            "remove_keys": keys,
+            "unbind": keys,
+            "clear": {
+                "type": "array",
+                "items": { "enum": Field::NAMES },
+                "description": "Fields to reset instead of keeping their default",
+            },
+        },
+        "additionalProperties": false,
+    })
+}
+
+/// The schema of one action: `entry` annotated with the action's defaults.
+fn property(item: &Item, entry: &Value) -> Value {
+    let mut property = entry.clone();
+    if !item.description.is_empty() {
+        property["description"] = item.description.clone().into();
+    }
+    property["default"] = json!({ "keys": item.keys });
+
+    property
+}
+
+/// The name of an action as written in the config file.
+fn name<T: Serialize>(t: &T) -> Result<String, serde_json::Error> {
+    Ok(match serde_json::to_value(t)? {
+        Value::String(name) => name,
+        value => value.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize, Clone, PartialEq)]
+    enum Action {
+        Quit,
+        Jump,
+    }
+
+    impl KeyMapConfig<Action> for Action {
+        fn keymap_config() -> Config<Action> {
+            Config::new(vec![
+                (Action::Quit, Action::Quit.keymap_item()),
+                (Action::Jump, Action::Jump.keymap_item()),
+            ])
+        }
+
+        fn keymap_item(&self) -> Item {
+            match self {
+                Action::Quit => Item::new(vec!["q".into(), "esc".into()], "Quit".into()),
+                Action::Jump => Item::new(vec!["g g".into()], "".into()),
+            }
+        }
+    }
+
+    #[test]
+    fn test_json_schema() {
+        let schema = json_schema::<Action>().unwrap();
+
+        let properties = schema["properties"].as_object().unwrap();
+        assert_eq!(properties.keys().collect::<Vec<_>>(), vec!["Jump", "Quit"]);
+        assert_eq!(properties["Quit"]["description"], "Quit");
+        assert_eq!(
+            properties["Quit"]["default"],
+            json!({ "keys": ["q", "esc"] })
+        );
+        assert_eq!(properties["Jump"]["oneOf"][0]["type"], "boolean");
+        assert_eq!(schema["additionalProperties"], false);
+
+        let fields = schema["definitions"]["item"]["properties"]
+            .as_object()
+            .unwrap();
+        for field in [
+            "keys", "when", "group", "order", "add_keys", "unbind", "clear",
+        ] {
+            assert!(fields.contains_key(field), "{field}");
+        }
+        assert_eq!(schema["definitions"]["key"]["pattern"], parser::pattern());
+    }
+
+    #[test]
+    fn test_config_json_schema() {
+        let config: Config<String> = toml::from_str(
+            r#"
+            Quit = { keys = ["q"] }
+            "#,
+        )
+        .unwrap();
+
+        let schema = config.json_schema().unwrap();
+        let quit = &schema["properties"]["Quit"];
+        assert_eq!(quit["allOf"][0]["$ref"], "#/definitions/item");
+        assert!(quit.get("description").is_none());
+    }
+
+    #[test]
+    fn test_json_schema_error() {
+        struct Invalid;
+
+        impl Serialize for Invalid {
+            fn serialize<S: serde::Serializer>(&self, _: S) -> Result<S::Ok, S::Error> {
+                Err(serde::ser::Error::custom("not a name"))
+            }
+        }
+
+        let config = Config::new(vec![(Invalid, Item::new(vec!["q".into()], "".into()))]);
+        let err = config.json_schema().unwrap_err();
+        assert_eq!(err.to_string(), "not a name");
+    }
+}